@@ -0,0 +1,588 @@
+use std::path::PathBuf;
+
+use amm_client::{decode_pool, instructions, CurveType, PoolKeys};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token_interface::Mint;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+/// Operate amm pools, every amount is in base units of its token
+#[derive(Parser)]
+#[command(name = "amm-cli", version)]
+struct Cli {
+    /// RPC URL of the cluster
+    #[arg(
+        long,
+        short = 'u',
+        env = "AMM_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Keypair signing and paying for the transactions [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', env = "AMM_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Token program owning the pool's mints
+    #[arg(long, default_value_t = anchor_spl::token::ID)]
+    token_program: Pubkey,
+
+    /// Simulate the transaction and print its logs instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Pool of a pair, fee tier and curve, the mints can be passed in any order
+#[derive(Args)]
+struct PoolArgs {
+    #[arg(long)]
+    mint_a: Pubkey,
+
+    #[arg(long)]
+    mint_b: Pubkey,
+
+    #[arg(long, default_value_t = 30)]
+    fee_bps: u16,
+
+    /// Curve of the pool [default: constant-product, stable-swap for the amp commands, create picks it
+    /// from --amp and --weight-a-bps]
+    #[arg(long, value_enum)]
+    curve: Option<Curve>,
+}
+
+/// Pricing curve of a pool, every curve has its own pool per pair and fee tier
+#[derive(Clone, Copy, ValueEnum)]
+enum Curve {
+    ConstantProduct,
+    StableSwap,
+    Weighted,
+    Concentrated,
+}
+
+impl From<Curve> for CurveType {
+    fn from(curve: Curve) -> Self {
+        match curve {
+            Curve::ConstantProduct => CurveType::ConstantProduct,
+            Curve::StableSwap => CurveType::StableSwap,
+            Curve::Weighted => CurveType::Weighted,
+            Curve::Concentrated => CurveType::Concentrated,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a pool and seed it with its initial liquidity
+    Create {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        amount_a: u64,
+
+        #[arg(long)]
+        amount_b: u64,
+
+        /// Amplification coefficient of a StableSwap pool, 0 creates a constant product pool
+        #[arg(long, default_value_t = 0)]
+        amp: u64,
+
+        /// Share of the value held in `mint_a` (in basis points) of a weighted pool, 0 for equal value
+        #[arg(long, default_value_t = 0, conflicts_with = "amp")]
+        weight_a_bps: u16,
+    },
+
+    /// Deposit at most `amount_a` and `amount_b` at the pool's current ratio
+    Add {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        amount_a: u64,
+
+        #[arg(long)]
+        amount_b: u64,
+
+        /// Minimum LP tokens minted, the deposit fails below it
+        #[arg(long)]
+        min_lp: u64,
+
+        /// Unix timestamp after which the transaction is rejected
+        #[arg(long)]
+        deadline: Option<i64>,
+    },
+
+    /// Burn LP tokens for the matching share of the reserves
+    Remove {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        lp_amount: u64,
+
+        /// Minimum amount of `mint_a` paid out, the withdrawal fails below it
+        #[arg(long)]
+        min_a: u64,
+
+        /// Minimum amount of `mint_b` paid out, the withdrawal fails below it
+        #[arg(long)]
+        min_b: u64,
+
+        /// Unix timestamp after which the transaction is rejected
+        #[arg(long)]
+        deadline: Option<i64>,
+    },
+
+    /// Swap an exact amount of `input_mint` for `output_mint`
+    Swap {
+        #[arg(long)]
+        input_mint: Pubkey,
+
+        #[arg(long)]
+        output_mint: Pubkey,
+
+        #[arg(long, default_value_t = 30)]
+        fee_bps: u16,
+
+        #[arg(long, value_enum, default_value_t = Curve::ConstantProduct)]
+        curve: Curve,
+
+        #[arg(long)]
+        amount_in: u64,
+
+        /// Minimum amount of `output_mint` received, the swap fails below it
+        #[arg(long)]
+        min_out: u64,
+
+        /// Unix timestamp after which the transaction is rejected
+        #[arg(long)]
+        deadline: Option<i64>,
+    },
+
+    /// Ramp a stable pool's amplification coefficient to `target_amp`, admin only
+    RampAmp {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        target_amp: u64,
+
+        /// Unix timestamp the ramp ends at, at least a day from now
+        #[arg(long)]
+        stop_ts: i64,
+    },
+
+    /// Freeze a stable pool's amplification coefficient where its ramp got to
+    StopRampAmp {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+
+    /// Print the pool's reserves, LP supply, implied price, curve and fee
+    Show {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+}
+
+impl PoolArgs {
+    // Keys of the pool on `--curve`, or on `default` when it isn't passed
+    fn keys(&self, token_program: &Pubkey, default: CurveType) -> PoolKeys {
+        let curve_type = self.curve.map_or(default, CurveType::from);
+        PoolKeys::new(
+            &self.mint_a,
+            &self.mint_b,
+            self.fee_bps,
+            curve_type,
+            token_program,
+        )
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+    if let Command::Show { pool } = &cli.command {
+        return show(
+            &rpc,
+            &pool.keys(&cli.token_program, CurveType::ConstantProduct),
+        );
+    }
+
+    let payer = load_keypair(cli.keypair.as_ref())?;
+    let instruction = build_instruction(&cli.command, &payer.pubkey(), &cli.token_program)?;
+
+    submit(&rpc, &payer, &[instruction], cli.dry_run)
+}
+
+// Maps the arguments of a command to its instruction, the amounts follow the mints as passed and are put
+// in the pool's canonical order
+fn build_instruction(
+    command: &Command,
+    user: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<Instruction> {
+    let instruction = match command {
+        Command::Create {
+            pool,
+            amount_a,
+            amount_b,
+            amp,
+            weight_a_bps,
+        } => {
+            // The curve follows from the amp and weight, `--curve` can only repeat it
+            let curve_type = CurveType::from_params(*amp, *weight_a_bps);
+            if pool
+                .curve
+                .is_some_and(|curve| CurveType::from(curve) != curve_type)
+            {
+                bail!("--curve doesn't match the curve picked by --amp and --weight-a-bps");
+            }
+
+            // Amounts and weight follow the mints as passed, the pool stores them in canonical order
+            let keys = pool.keys(token_program, curve_type);
+            let (amount_a, amount_b, weight_a_bps) = match keys.token_a_mint == pool.mint_a {
+                true => (*amount_a, *amount_b, *weight_a_bps),
+                false if *weight_a_bps == 0 => (*amount_b, *amount_a, 0),
+                false => (
+                    *amount_b,
+                    *amount_a,
+                    10_000u16.saturating_sub(*weight_a_bps),
+                ),
+            };
+            instructions::initialize_pool(user, &keys, amount_a, amount_b, *amp, weight_a_bps)
+        }
+        Command::Add {
+            pool,
+            amount_a,
+            amount_b,
+            min_lp,
+            deadline,
+        } => {
+            let keys = pool.keys(token_program, CurveType::ConstantProduct);
+            reject_concentrated(&keys, "add")?;
+            let (amount_a, amount_b) = match keys.token_a_mint == pool.mint_a {
+                true => (*amount_a, *amount_b),
+                false => (*amount_b, *amount_a),
+            };
+            instructions::add_liquidity(user, &keys, amount_a, amount_b, *min_lp, *deadline)
+        }
+        Command::Remove {
+            pool,
+            lp_amount,
+            min_a,
+            min_b,
+            deadline,
+        } => {
+            let keys = pool.keys(token_program, CurveType::ConstantProduct);
+            reject_concentrated(&keys, "remove")?;
+            let (min_a, min_b) = match keys.token_a_mint == pool.mint_a {
+                true => (*min_a, *min_b),
+                false => (*min_b, *min_a),
+            };
+            instructions::remove_liquidity(user, &keys, *lp_amount, min_a, min_b, *deadline)
+        }
+        Command::Swap {
+            input_mint,
+            output_mint,
+            fee_bps,
+            curve,
+            amount_in,
+            min_out,
+            deadline,
+        } => {
+            let keys = PoolKeys::new(
+                input_mint,
+                output_mint,
+                *fee_bps,
+                CurveType::from(*curve),
+                token_program,
+            );
+            reject_concentrated(&keys, "swap")?;
+            let a_to_b = keys.a_to_b(input_mint);
+            instructions::swap(user, &keys, *amount_in, *min_out, a_to_b, *deadline)
+        }
+        Command::RampAmp {
+            pool,
+            target_amp,
+            stop_ts,
+        } => {
+            let keys = pool.keys(token_program, CurveType::StableSwap);
+            instructions::ramp_amp(user, &keys, *target_amp, *stop_ts)
+        }
+        Command::StopRampAmp { pool } => {
+            instructions::stop_ramp_amp(user, &pool.keys(token_program, CurveType::StableSwap))
+        }
+        Command::Show { .. } => unreachable!(),
+    };
+
+    Ok(instruction)
+}
+
+// Concentrated liquidity pools trade across tick arrays and hold liquidity in positions, the program
+// rejects the reserve and LP token instructions on them
+fn reject_concentrated(keys: &PoolKeys, command: &str) -> Result<()> {
+    if keys.curve_type == CurveType::Concentrated {
+        bail!("`{command}` doesn't work on concentrated liquidity pools, their liquidity is in positions");
+    }
+    Ok(())
+}
+
+fn load_keypair(path: Option<&PathBuf>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => {
+            let home = std::env::var("HOME").context("HOME is not set, pass --keypair")?;
+            PathBuf::from(home).join(".config/solana/id.json")
+        }
+    };
+
+    read_keypair_file(&path).map_err(|err| anyhow!("reading keypair {}: {err}", path.display()))
+}
+
+fn submit(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    dry_run: bool,
+) -> Result<()> {
+    let blockhash = rpc.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    );
+
+    if !dry_run {
+        let signature = rpc.send_and_confirm_transaction(&transaction)?;
+        println!("{signature}");
+        return Ok(());
+    }
+
+    let simulation = rpc.simulate_transaction(&transaction)?.value;
+    for log in simulation.logs.unwrap_or_default() {
+        println!("{log}");
+    }
+    if let Some(units) = simulation.units_consumed {
+        println!("compute units: {units}");
+    }
+    match simulation.err {
+        Some(err) => bail!("simulation failed: {err}"),
+        None => {
+            println!("simulation succeeded, nothing was sent");
+            Ok(())
+        }
+    }
+}
+
+fn show(rpc: &RpcClient, keys: &PoolKeys) -> Result<()> {
+    let pool_account = rpc
+        .get_account(&keys.pool)
+        .with_context(|| format!("pool {} not found", keys.pool))?;
+    let pool = decode_pool(&pool_account.data)?;
+
+    // Concentrated liquidity pools have positions instead of an LP mint
+    let mint_keys = match pool.is_concentrated() {
+        true => vec![keys.token_a_mint, keys.token_b_mint],
+        false => vec![keys.token_a_mint, keys.token_b_mint, keys.lp_mint],
+    };
+    let mints = rpc.get_multiple_accounts(&mint_keys)?;
+    let mut mints = mints.into_iter().map(|account| {
+        let account = account.ok_or_else(|| anyhow!("mint account not found"))?;
+        Ok::<_, anyhow::Error>(Mint::try_deserialize(&mut &account.data[..])?)
+    });
+    let mint_a = mints.next().unwrap()?;
+    let mint_b = mints.next().unwrap()?;
+    let lp_mint = mints.next().transpose()?;
+
+    println!("pool           {}", keys.pool);
+    println!(
+        "token A        {} ({} decimals)",
+        pool.token_a_mint, mint_a.decimals
+    );
+    println!(
+        "token B        {} ({} decimals)",
+        pool.token_b_mint, mint_b.decimals
+    );
+    println!("reserve A      {}", pool.reserve_a);
+    println!("reserve B      {}", pool.reserve_b);
+    if let Some(lp_mint) = lp_mint {
+        println!("lp mint        {}", keys.lp_mint);
+        println!("lp supply      {}", lp_mint.supply);
+    }
+    // The program's own Q64.64 spot price, the one `quote_spot_price` returns. It only becomes a float for
+    // display, scaled from base units to whole tokens
+    let now = rpc.get_block_time(rpc.get_slot()?)?;
+    match pool.spot_price(now) {
+        Ok(price) => {
+            let decimals = mint_a.decimals as i32 - mint_b.decimals as i32;
+            let price = price as f64 / 2f64.powi(64) * 10f64.powi(decimals);
+            println!("price          1 A = {price} B");
+        }
+        Err(_) => println!("price          -"),
+    }
+    match pool.curve_type {
+        CurveType::StableSwap => {
+            println!("curve          stable swap (amp {})", pool.current_amp(now));
+            if now < pool.ramp_stop_ts {
+                println!(
+                    "amp ramp       {} -> {} from {} to {}",
+                    pool.initial_amp, pool.target_amp, pool.ramp_start_ts, pool.ramp_stop_ts
+                );
+            }
+        }
+        CurveType::Weighted => println!(
+            "curve          weighted ({}/{})",
+            pool.weight_a_bps as f64 / 100.0,
+            pool.weight_b_bps as f64 / 100.0
+        ),
+        CurveType::Concentrated => {
+            println!(
+                "curve          concentrated liquidity (tick spacing {})",
+                pool.tick_spacing
+            );
+            println!("current tick   {}", pool.tick_current);
+            println!("liquidity      {}", pool.liquidity);
+        }
+        CurveType::ConstantProduct => println!("curve          constant product"),
+    }
+    println!("fee            {} bps", pool.fee_bps);
+    println!(
+        "protocol fees  {} A / {} B",
+        pool.protocol_fees_a, pool.protocol_fees_b
+    );
+    println!("status         {:#05b}", pool.status);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two mints, sorted the way the pool stores them
+    fn mints() -> (Pubkey, Pubkey) {
+        let (mint_x, mint_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        match mint_x < mint_y {
+            true => (mint_x, mint_y),
+            false => (mint_y, mint_x),
+        }
+    }
+
+    // Parses the arguments the way the binary does and builds the instruction of the command
+    fn build(args: &str) -> Result<Instruction> {
+        let cli = Cli::try_parse_from(["amm-cli"].into_iter().chain(args.split_whitespace()))?;
+        build_instruction(&cli.command, &Pubkey::default(), &cli.token_program)
+    }
+
+    fn keys(mint_a: &Pubkey, mint_b: &Pubkey, curve_type: CurveType) -> PoolKeys {
+        PoolKeys::new(mint_a, mint_b, 30, curve_type, &anchor_spl::token::ID)
+    }
+
+    #[test]
+    fn add_and_remove_follow_the_mints_as_passed() {
+        let (low, high) = mints();
+        let keys = keys(&low, &high, CurveType::ConstantProduct);
+        let user = Pubkey::default();
+
+        // Passed the other way around, amounts and minimums are swapped into the canonical order
+        let add = build(&format!(
+            "add --mint-a {high} --mint-b {low} --amount-a 1 --amount-b 2 --min-lp 3"
+        ))
+        .unwrap();
+        assert_eq!(
+            add,
+            instructions::add_liquidity(&user, &keys, 2, 1, 3, None)
+        );
+
+        let remove = build(&format!(
+            "remove --mint-a {high} --mint-b {low} --lp-amount 5 --min-a 1 --min-b 2 --deadline 9"
+        ))
+        .unwrap();
+        assert_eq!(
+            remove,
+            instructions::remove_liquidity(&user, &keys, 5, 2, 1, Some(9))
+        );
+
+        let add = build(&format!(
+            "add --mint-a {low} --mint-b {high} --amount-a 1 --amount-b 2 --min-lp 3"
+        ))
+        .unwrap();
+        assert_eq!(
+            add,
+            instructions::add_liquidity(&user, &keys, 1, 2, 3, None)
+        );
+    }
+
+    #[test]
+    fn swap_direction_follows_the_input_mint() {
+        let (low, high) = mints();
+        let user = Pubkey::default();
+
+        let swap = |input: &Pubkey, output: &Pubkey, curve: &str| {
+            build(&format!(
+                "swap --input-mint {input} --output-mint {output} --curve {curve} --amount-in 100 --min-out 90"
+            ))
+        };
+        let constant_product = keys(&low, &high, CurveType::ConstantProduct);
+        assert_eq!(
+            swap(&low, &high, "constant-product").unwrap(),
+            instructions::swap(&user, &constant_product, 100, 90, true, None)
+        );
+        assert_eq!(
+            swap(&high, &low, "constant-product").unwrap(),
+            instructions::swap(&user, &constant_product, 100, 90, false, None)
+        );
+
+        let stable = keys(&low, &high, CurveType::StableSwap);
+        assert_eq!(
+            swap(&high, &low, "stable-swap").unwrap(),
+            instructions::swap(&user, &stable, 100, 90, false, None)
+        );
+    }
+
+    #[test]
+    fn create_flips_the_weight_with_the_pair() {
+        let (low, high) = mints();
+        let keys = keys(&low, &high, CurveType::Weighted);
+
+        let create = build(&format!(
+            "create --mint-a {high} --mint-b {low} --amount-a 1 --amount-b 2 --weight-a-bps 8000"
+        ))
+        .unwrap();
+        assert_eq!(
+            create,
+            instructions::initialize_pool(&Pubkey::default(), &keys, 2, 1, 0, 2_000)
+        );
+
+        let mismatch = build(&format!(
+            "create --mint-a {low} --mint-b {high} --amount-a 1 --amount-b 2 --amp 100 --curve weighted"
+        ));
+        assert!(mismatch.is_err());
+    }
+
+    #[test]
+    fn concentrated_pools_are_rejected_before_sending() {
+        let (low, high) = mints();
+
+        for command in [
+            format!("add --mint-a {low} --mint-b {high} --curve concentrated --amount-a 1 --amount-b 2 --min-lp 0"),
+            format!("remove --mint-a {low} --mint-b {high} --curve concentrated --lp-amount 1 --min-a 0 --min-b 0"),
+            format!("swap --input-mint {low} --output-mint {high} --curve concentrated --amount-in 1 --min-out 0"),
+        ] {
+            let err = build(&command).unwrap_err();
+            assert!(err.to_string().contains("concentrated liquidity pools"));
+        }
+    }
+}
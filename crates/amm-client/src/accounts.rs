@@ -0,0 +1,22 @@
+use amm::state::{AmmConfig, Pool, Position, TickArray};
+use anchor_lang::prelude::*;
+
+/// Decodes a `Pool` account, discriminator included
+pub fn decode_pool(data: &[u8]) -> Result<Pool> {
+    Pool::try_deserialize(&mut &data[..])
+}
+
+/// Decodes the `AmmConfig` account, discriminator included
+pub fn decode_config(data: &[u8]) -> Result<AmmConfig> {
+    AmmConfig::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a concentrated liquidity `Position` account, discriminator included
+pub fn decode_position(data: &[u8]) -> Result<Position> {
+    Position::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a concentrated liquidity `TickArray` account, discriminator included
+pub fn decode_tick_array(data: &[u8]) -> Result<TickArray> {
+    TickArray::try_deserialize(&mut &data[..])
+}
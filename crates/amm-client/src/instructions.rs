@@ -0,0 +1,1049 @@
+use amm::state::{CurveType, Pool, TickArray};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable::get_program_data_address;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+
+use crate::pda::{
+    canonical_pair, config_address, locked_lp_address, lp_mint_address, pool_address,
+    position_address, tick_array_address, vault_a_address, vault_b_address,
+};
+
+/// Every address of one pool, derived from its pair, fee tier and curve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub fee_bps: u16,
+    pub curve_type: CurveType,
+    pub token_program: Pubkey,
+    pub config: Pubkey,
+    pub pool: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub lp_mint: Pubkey,
+}
+
+impl PoolKeys {
+    /// Derives the pool of `mint_x` / `mint_y`, the mints can be passed in any order
+    pub fn new(
+        mint_x: &Pubkey,
+        mint_y: &Pubkey,
+        fee_bps: u16,
+        curve_type: CurveType,
+        token_program: &Pubkey,
+    ) -> Self {
+        let (token_a_mint, token_b_mint, _) = canonical_pair(mint_x, mint_y);
+        let (pool, _) = pool_address(&token_a_mint, &token_b_mint, fee_bps, curve_type);
+
+        Self {
+            token_a_mint,
+            token_b_mint,
+            fee_bps,
+            curve_type,
+            token_program: *token_program,
+            config: config_address().0,
+            pool,
+            vault_a: vault_a_address(&pool).0,
+            vault_b: vault_b_address(&pool).0,
+            lp_mint: lp_mint_address(&pool).0,
+        }
+    }
+
+    /// Keys of an already decoded pool account
+    pub fn from_pool(pool: &Pool, token_program: &Pubkey) -> Self {
+        Self::new(
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            pool.fee_bps,
+            pool.curve_type,
+            token_program,
+        )
+    }
+
+    /// Direction of a swap selling `input_mint`
+    pub fn a_to_b(&self, input_mint: &Pubkey) -> bool {
+        *input_mint == self.token_a_mint
+    }
+
+    /// Associated token account of `owner` for `mint`, under the pool's token program
+    pub fn associated_token_address(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.token_program)
+    }
+}
+
+/// Every address of one position of a concentrated liquidity pool, derived from its owner and ticks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionKeys {
+    pub position: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub tick_array_lower: Pubkey,
+    pub tick_array_upper: Pubkey,
+}
+
+impl PositionKeys {
+    pub fn new(
+        keys: &PoolKeys,
+        owner: &Pubkey,
+        tick_spacing: u16,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Self {
+        let tick_array = |tick_index: i32| {
+            let start_tick_index = TickArray::start_tick_index(tick_index, tick_spacing);
+            tick_array_address(&keys.pool, start_tick_index).0
+        };
+
+        Self {
+            position: position_address(&keys.pool, owner, tick_lower, tick_upper).0,
+            tick_lower,
+            tick_upper,
+            tick_array_lower: tick_array(tick_lower),
+            tick_array_upper: tick_array(tick_upper),
+        }
+    }
+}
+
+/// Tick arrays a swap can cross, `count` of them starting with the one it begins in
+pub fn swap_tick_arrays(
+    keys: &PoolKeys,
+    tick_spacing: u16,
+    tick_current: i32,
+    a_to_b: bool,
+    count: usize,
+) -> Vec<Pubkey> {
+    // Going up the search starts past the current tick, possibly in the next array
+    let ticks_per_array = TickArray::ticks_per_array(tick_spacing);
+    let first = match a_to_b {
+        true => TickArray::start_tick_index(tick_current, tick_spacing),
+        false => TickArray::start_tick_index(tick_current + tick_spacing as i32, tick_spacing),
+    };
+    (0..count as i32)
+        .map(|index| match a_to_b {
+            true => first - index * ticks_per_array,
+            false => first + index * ticks_per_array,
+        })
+        .map(|start_tick_index| tick_array_address(&keys.pool, start_tick_index).0)
+        .collect()
+}
+
+/// Creates the singleton config, `admin` has to be the program's upgrade authority and becomes its admin and guardian
+pub fn initialize_config(admin: &Pubkey, treasury: &Pubkey) -> Instruction {
+    let accounts = amm::accounts::InitializeConfig {
+        admin: *admin,
+        amm_config: config_address().0,
+        program_data: get_program_data_address(&amm::ID),
+        system_program: system_program::ID,
+    };
+    let data = amm::instruction::InitializeConfig {
+        treasury: *treasury,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Proposes `new_admin` as the config's admin, it takes over once it accepts
+pub fn set_admin(admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    let accounts = amm::accounts::SetAdmin {
+        admin: *admin,
+        amm_config: config_address().0,
+    };
+    let data = amm::instruction::SetAdmin {
+        new_admin: *new_admin,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Makes the pending admin proposed with `set_admin` the config's admin
+pub fn accept_admin(pending_admin: &Pubkey) -> Instruction {
+    let accounts = amm::accounts::AcceptAdmin {
+        pending_admin: *pending_admin,
+        amm_config: config_address().0,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: amm::instruction::AcceptAdmin {}.data(),
+    }
+}
+
+/// Replaces the config fields that are `Some`, admin only
+pub fn update_config(
+    admin: &Pubkey,
+    fee_tiers: Option<[u16; 4]>,
+    protocol_fee_bps: Option<u16>,
+    treasury: Option<Pubkey>,
+    guardian: Option<Pubkey>,
+) -> Instruction {
+    let accounts = amm::accounts::UpdateConfig {
+        admin: *admin,
+        amm_config: config_address().0,
+    };
+    let data = amm::instruction::UpdateConfig {
+        fee_tiers,
+        protocol_fee_bps,
+        treasury,
+        guardian,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Sets the status flags of one pool, admin or guardian
+pub fn set_pool_status(authority: &Pubkey, keys: &PoolKeys, status: u8) -> Instruction {
+    let accounts = amm::accounts::SetPoolStatus {
+        authority: *authority,
+        amm_config: keys.config,
+        liquidity_pool: keys.pool,
+    };
+    let data = amm::instruction::SetPoolStatus { status };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Sets the status flags of every pool at once, admin or guardian
+pub fn set_global_status(authority: &Pubkey, status: u8) -> Instruction {
+    let accounts = amm::accounts::SetGlobalStatus {
+        authority: *authority,
+        amm_config: config_address().0,
+    };
+    let data = amm::instruction::SetGlobalStatus { status };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Creates the pool and seeds it from the creator's associated token accounts
+///
+/// A non-zero `amp` creates a StableSwap pool with that amplification coefficient, a non-zero
+/// `weight_a_bps` a weighted pool holding that share of its value in token A, both 0 a constant product one
+pub fn initialize_pool(
+    creator: &Pubkey,
+    keys: &PoolKeys,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    amp: u64,
+    weight_a_bps: u16,
+) -> Instruction {
+    let accounts = amm::accounts::InitializePool {
+        creator: *creator,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        liquidity_pool: keys.pool,
+        creator_token_a: keys.associated_token_address(creator, &keys.token_a_mint),
+        creator_token_b: keys.associated_token_address(creator, &keys.token_b_mint),
+        lp_mint: keys.lp_mint,
+        locked_lp_token: locked_lp_address(&keys.lp_mint).0,
+        creator_lp_token: keys.associated_token_address(creator, &keys.lp_mint),
+        system_program: system_program::ID,
+        token_program: keys.token_program,
+        associated_token_program: associated_token::ID,
+    };
+    let data = amm::instruction::InitializePool {
+        token_a_amount,
+        token_b_amount,
+        fee_bps: keys.fee_bps,
+        amp,
+        weight_a_bps,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn swap_accounts(user: &Pubkey, keys: &PoolKeys) -> Vec<AccountMeta> {
+    amm::accounts::Swap {
+        signer: *user,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        swap_account_a: keys.associated_token_address(user, &keys.token_a_mint),
+        swap_account_b: keys.associated_token_address(user, &keys.token_b_mint),
+        system_program: system_program::ID,
+        token_program: keys.token_program,
+        associated_token_program: associated_token::ID,
+    }
+    .to_account_metas(None)
+}
+
+/// Swaps an exact `amount_in`, token A for token B when `a_to_b` and the other way around otherwise
+pub fn swap(
+    user: &Pubkey,
+    keys: &PoolKeys,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    a_to_b: bool,
+    deadline: Option<i64>,
+) -> Instruction {
+    let data = amm::instruction::Swap {
+        amount_in,
+        minimum_amount_out,
+        a_to_b,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: swap_accounts(user, keys),
+        data: data.data(),
+    }
+}
+
+/// Swaps for an exact `amount_out`, paying at most `maximum_amount_in` for it
+pub fn swap_exact_out(
+    user: &Pubkey,
+    keys: &PoolKeys,
+    amount_out: u64,
+    maximum_amount_in: u64,
+    a_to_b: bool,
+    deadline: Option<i64>,
+) -> Instruction {
+    let data = amm::instruction::SwapExactOut {
+        amount_out,
+        maximum_amount_in,
+        a_to_b,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: swap_accounts(user, keys),
+        data: data.data(),
+    }
+}
+
+/// Deposits at most `amount_a` and `amount_b` at the pool's current ratio
+pub fn add_liquidity(
+    provider: &Pubkey,
+    keys: &PoolKeys,
+    amount_a: u64,
+    amount_b: u64,
+    minimum_lp_tokens: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    let accounts = amm::accounts::AddLiquidity {
+        liquidity_provider: *provider,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        provider_token_a: keys.associated_token_address(provider, &keys.token_a_mint),
+        provider_token_b: keys.associated_token_address(provider, &keys.token_b_mint),
+        lp_mint: keys.lp_mint,
+        provider_token_lp: keys.associated_token_address(provider, &keys.lp_mint),
+        system_program: system_program::ID,
+        token_program: keys.token_program,
+        associated_token_program: associated_token::ID,
+    };
+    let data = amm::instruction::AddLiquidity {
+        amount_a,
+        amount_b,
+        minimum_lp_tokens,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Burns `lp_amount` LP tokens for the matching share of the reserves
+pub fn remove_liquidity(
+    owner: &Pubkey,
+    keys: &PoolKeys,
+    lp_amount: u64,
+    minimum_token_a: u64,
+    minimum_token_b: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    let accounts = amm::accounts::RemoveLiquidity {
+        liquidity_revoker: *owner,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        lp_mint: keys.lp_mint,
+        revoker_token_a: keys.associated_token_address(owner, &keys.token_a_mint),
+        revoker_token_b: keys.associated_token_address(owner, &keys.token_b_mint),
+        revoker_token_lp: keys.associated_token_address(owner, &keys.lp_mint),
+        system_program: system_program::ID,
+        token_program: keys.token_program,
+        associated_token_program: associated_token::ID,
+    };
+    let data = amm::instruction::RemoveLiquidity {
+        lp_amount,
+        minimum_token_a,
+        minimum_token_b,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Sets the pool's reserves to its vault balances net of the protocol fees, anyone can send it
+pub fn sync(keys: &PoolKeys) -> Instruction {
+    let accounts = amm::accounts::SyncReserves {
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: amm::instruction::Sync {}.data(),
+    }
+}
+
+/// Sends the vault balances above the reserves and protocol fees to the associated token accounts of `recipient`
+pub fn skim(skimmer: &Pubkey, keys: &PoolKeys, recipient: &Pubkey) -> Instruction {
+    let accounts = amm::accounts::Skim {
+        skimmer: *skimmer,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        recipient_token_a: keys.associated_token_address(recipient, &keys.token_a_mint),
+        recipient_token_b: keys.associated_token_address(recipient, &keys.token_b_mint),
+        token_program: keys.token_program,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: amm::instruction::Skim {}.data(),
+    }
+}
+
+/// Pays the pool's protocol fees out to the associated token accounts of the config's `treasury`
+pub fn collect_protocol_fees(
+    collector: &Pubkey,
+    keys: &PoolKeys,
+    treasury: &Pubkey,
+) -> Instruction {
+    let accounts = amm::accounts::CollectProtocolFees {
+        collector: *collector,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        treasury: *treasury,
+        treasury_token_a: keys.associated_token_address(treasury, &keys.token_a_mint),
+        treasury_token_b: keys.associated_token_address(treasury, &keys.token_b_mint),
+        system_program: system_program::ID,
+        token_program: keys.token_program,
+        associated_token_program: associated_token::ID,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: amm::instruction::CollectProtocolFees {}.data(),
+    }
+}
+
+fn quote_accounts(keys: &PoolKeys) -> Vec<AccountMeta> {
+    amm::accounts::Quote {
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        lp_mint: keys.lp_mint,
+    }
+    .to_account_metas(None)
+}
+
+/// Quotes a swap of `amount_in` without sending it, the result comes back as return data
+pub fn quote_swap(keys: &PoolKeys, amount_in: u64, a_to_b: bool) -> Instruction {
+    let data = amm::instruction::QuoteSwap { amount_in, a_to_b };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: quote_accounts(keys),
+        data: data.data(),
+    }
+}
+
+/// Quotes a deposit of at most `amount_a` and `amount_b`, the result comes back as return data
+pub fn quote_add_liquidity(keys: &PoolKeys, amount_a: u64, amount_b: u64) -> Instruction {
+    let data = amm::instruction::QuoteAddLiquidity { amount_a, amount_b };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: quote_accounts(keys),
+        data: data.data(),
+    }
+}
+
+/// Quotes burning `lp_amount` LP tokens, the result comes back as return data
+pub fn quote_remove_liquidity(keys: &PoolKeys, lp_amount: u64) -> Instruction {
+    let data = amm::instruction::QuoteRemoveLiquidity { lp_amount };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: quote_accounts(keys),
+        data: data.data(),
+    }
+}
+
+/// Quotes the pool's spot price, the result comes back as return data
+pub fn quote_spot_price(keys: &PoolKeys) -> Instruction {
+    let accounts = amm::accounts::QuoteSpotPrice {
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: amm::instruction::QuoteSpotPrice {}.data(),
+    }
+}
+
+/// Ramps a stable pool's amplification coefficient linearly to `target_amp` by `ramp_stop_ts`, admin only
+pub fn ramp_amp(
+    admin: &Pubkey,
+    keys: &PoolKeys,
+    target_amp: u64,
+    ramp_stop_ts: i64,
+) -> Instruction {
+    let accounts = amm::accounts::RampAmp {
+        admin: *admin,
+        amm_config: keys.config,
+        liquidity_pool: keys.pool,
+    };
+    let data = amm::instruction::RampAmp {
+        target_amp,
+        ramp_stop_ts,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Freezes a stable pool's amplification coefficient at its current value, admin or guardian
+pub fn stop_ramp_amp(authority: &Pubkey, keys: &PoolKeys) -> Instruction {
+    let accounts = amm::accounts::StopRampAmp {
+        authority: *authority,
+        amm_config: keys.config,
+        liquidity_pool: keys.pool,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: amm::instruction::StopRampAmp {}.data(),
+    }
+}
+
+/// Creates a concentrated liquidity pool at `sqrt_price` (Q64.64), without liquidity until positions add some
+pub fn initialize_concentrated_pool(
+    creator: &Pubkey,
+    keys: &PoolKeys,
+    tick_spacing: u16,
+    sqrt_price: u128,
+) -> Instruction {
+    let accounts = amm::accounts::InitializeConcentratedPool {
+        creator: *creator,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        liquidity_pool: keys.pool,
+        system_program: system_program::ID,
+        token_program: keys.token_program,
+    };
+    let data = amm::instruction::InitializeConcentratedPool {
+        fee_bps: keys.fee_bps,
+        tick_spacing,
+        sqrt_price,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Creates the tick array of a concentrated liquidity pool starting at `start_tick_index`, anyone can pay for it
+pub fn initialize_tick_array(
+    payer: &Pubkey,
+    keys: &PoolKeys,
+    start_tick_index: i32,
+) -> Instruction {
+    let accounts = amm::accounts::InitializeTickArray {
+        payer: *payer,
+        liquidity_pool: keys.pool,
+        tick_array: tick_array_address(&keys.pool, start_tick_index).0,
+        system_program: system_program::ID,
+    };
+    let data = amm::instruction::InitializeTickArray { start_tick_index };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Opens an empty position between the ticks of `position`
+pub fn open_position(owner: &Pubkey, keys: &PoolKeys, position: &PositionKeys) -> Instruction {
+    let accounts = amm::accounts::OpenPosition {
+        owner: *owner,
+        liquidity_pool: keys.pool,
+        position: position.position,
+        system_program: system_program::ID,
+    };
+    let data = amm::instruction::OpenPosition {
+        tick_lower: position.tick_lower,
+        tick_upper: position.tick_upper,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn modify_liquidity_accounts(
+    owner: &Pubkey,
+    keys: &PoolKeys,
+    position: &PositionKeys,
+) -> Vec<AccountMeta> {
+    amm::accounts::ModifyLiquidity {
+        owner: *owner,
+        amm_config: keys.config,
+        token_a_mint: keys.token_a_mint,
+        token_b_mint: keys.token_b_mint,
+        liquidity_pool: keys.pool,
+        position: position.position,
+        tick_array_lower: position.tick_array_lower,
+        tick_array_upper: position.tick_array_upper,
+        vault_a: keys.vault_a,
+        vault_b: keys.vault_b,
+        owner_token_a: keys.associated_token_address(owner, &keys.token_a_mint),
+        owner_token_b: keys.associated_token_address(owner, &keys.token_b_mint),
+        token_program: keys.token_program,
+    }
+    .to_account_metas(None)
+}
+
+/// Adds `liquidity` to a position, paying at most `maximum_token_a` and `maximum_token_b` for it
+pub fn increase_liquidity(
+    owner: &Pubkey,
+    keys: &PoolKeys,
+    position: &PositionKeys,
+    liquidity: u128,
+    maximum_token_a: u64,
+    maximum_token_b: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    let data = amm::instruction::IncreaseLiquidity {
+        liquidity,
+        maximum_token_a,
+        maximum_token_b,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: modify_liquidity_accounts(owner, keys, position),
+        data: data.data(),
+    }
+}
+
+/// Removes `liquidity` from a position and collects the fees it earned, a zero liquidity only collects them
+pub fn decrease_liquidity(
+    owner: &Pubkey,
+    keys: &PoolKeys,
+    position: &PositionKeys,
+    liquidity: u128,
+    minimum_token_a: u64,
+    minimum_token_b: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    let data = amm::instruction::DecreaseLiquidity {
+        liquidity,
+        minimum_token_a,
+        minimum_token_b,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts: modify_liquidity_accounts(owner, keys, position),
+        data: data.data(),
+    }
+}
+
+/// Swaps an exact `amount_in` on a concentrated liquidity pool across `tick_arrays`, see `swap_tick_arrays`
+pub fn swap_concentrated(
+    user: &Pubkey,
+    keys: &PoolKeys,
+    tick_arrays: &[Pubkey],
+    amount_in: u64,
+    minimum_amount_out: u64,
+    a_to_b: bool,
+    deadline: Option<i64>,
+) -> Instruction {
+    let mut accounts = swap_accounts(user, keys);
+    accounts.extend(
+        tick_arrays
+            .iter()
+            .map(|tick_array| AccountMeta::new(*tick_array, false)),
+    );
+    let data = amm::instruction::SwapConcentrated {
+        amount_in,
+        minimum_amount_out,
+        a_to_b,
+        deadline,
+    };
+
+    Instruction {
+        program_id: amm::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn pool_keys(curve_type: CurveType) -> PoolKeys {
+        PoolKeys::new(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            30,
+            curve_type,
+            &anchor_spl::token::ID,
+        )
+    }
+
+    // Checks the instruction against the order, signers (S) and writable accounts (W) of the program's struct
+    fn assert_accounts<T: Discriminator>(ix: &Instruction, expected: &[(Pubkey, &str)]) {
+        assert_eq!(ix.program_id, amm::ID);
+        assert!(ix.data.starts_with(T::DISCRIMINATOR));
+        let actual: Vec<_> = ix
+            .accounts
+            .iter()
+            .map(|meta| {
+                let flags = match (meta.is_signer, meta.is_writable) {
+                    (true, true) => "SW",
+                    (true, false) => "S",
+                    (false, true) => "W",
+                    (false, false) => "",
+                };
+                (meta.pubkey, flags)
+            })
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn pool_keys_sort_the_pair() {
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let token_program = anchor_spl::token::ID;
+
+        let curve_type = CurveType::ConstantProduct;
+        let keys = PoolKeys::new(&mint_x, &mint_y, 30, curve_type, &token_program);
+        assert_eq!(
+            keys,
+            PoolKeys::new(&mint_y, &mint_x, 30, curve_type, &token_program)
+        );
+        assert!(keys.token_a_mint < keys.token_b_mint);
+        assert!(keys.a_to_b(&keys.token_a_mint));
+        assert!(!keys.a_to_b(&keys.token_b_mint));
+        assert_ne!(
+            keys.pool,
+            PoolKeys::new(&mint_x, &mint_y, 5, curve_type, &token_program).pool
+        );
+
+        // Every curve gets its own pool for the same pair and fee tier
+        let stable = PoolKeys::new(&mint_x, &mint_y, 30, CurveType::StableSwap, &token_program);
+        assert_ne!(keys.pool, stable.pool);
+    }
+
+    #[test]
+    fn swap_targets_the_pool_accounts() {
+        let user = Pubkey::new_unique();
+        let keys = PoolKeys::new(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            30,
+            CurveType::ConstantProduct,
+            &anchor_spl::token::ID,
+        );
+
+        let ix = swap(&user, &keys, 1_000, 900, true, Some(42));
+        assert_eq!(ix.program_id, amm::ID);
+        assert!(ix.data.starts_with(amm::instruction::Swap::DISCRIMINATOR));
+
+        let signer = &ix.accounts[0];
+        assert!(signer.pubkey == user && signer.is_signer && signer.is_writable);
+        let pool = &ix.accounts[4];
+        assert!(pool.pubkey == keys.pool && pool.is_writable);
+    }
+
+    #[test]
+    fn position_keys_find_the_arrays_of_both_ticks() {
+        let owner = Pubkey::new_unique();
+        let keys = PoolKeys::new(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            30,
+            CurveType::Concentrated,
+            &anchor_spl::token::ID,
+        );
+
+        // 64 ticks apart, each array covers 32 of them
+        let position = PositionKeys::new(&keys, &owner, 64, -128, 1_984);
+        assert_eq!(
+            position.tick_array_lower,
+            tick_array_address(&keys.pool, -2_048).0
+        );
+        assert_eq!(
+            position.tick_array_upper,
+            tick_array_address(&keys.pool, 0).0
+        );
+        assert_ne!(
+            position.position,
+            PositionKeys::new(&keys, &Pubkey::new_unique(), 64, -128, 1_984).position
+        );
+
+        // Going up from the last tick of an array the swap starts in the next one
+        let arrays = swap_tick_arrays(&keys, 64, 2_047, false, 2);
+        assert_eq!(
+            arrays,
+            [
+                tick_array_address(&keys.pool, 2_048).0,
+                tick_array_address(&keys.pool, 4_096).0
+            ]
+        );
+        let ix = swap_concentrated(&owner, &keys, &arrays, 1_000, 0, false, None);
+        assert!(ix
+            .data
+            .starts_with(amm::instruction::SwapConcentrated::DISCRIMINATOR));
+        assert_eq!(ix.accounts.len(), 14);
+        assert!(ix.accounts[13].is_writable && !ix.accounts[13].is_signer);
+    }
+
+    #[test]
+    fn swap_exact_out_targets_the_swap_accounts() {
+        let user = Pubkey::new_unique();
+        let keys = pool_keys(CurveType::ConstantProduct);
+
+        let ix = swap_exact_out(&user, &keys, 1_000, 1_100, false, None);
+        assert_accounts::<amm::instruction::SwapExactOut>(
+            &ix,
+            &[
+                (user, "SW"),
+                (keys.config, ""),
+                (keys.token_a_mint, ""),
+                (keys.token_b_mint, ""),
+                (keys.pool, "W"),
+                (keys.vault_a, "W"),
+                (keys.vault_b, "W"),
+                (
+                    keys.associated_token_address(&user, &keys.token_a_mint),
+                    "W",
+                ),
+                (
+                    keys.associated_token_address(&user, &keys.token_b_mint),
+                    "W",
+                ),
+                (system_program::ID, ""),
+                (keys.token_program, ""),
+                (associated_token::ID, ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn quotes_only_read_the_pool() {
+        let keys = pool_keys(CurveType::StableSwap);
+        let quote = [
+            (keys.config, ""),
+            (keys.token_a_mint, ""),
+            (keys.token_b_mint, ""),
+            (keys.pool, ""),
+            (keys.lp_mint, ""),
+        ];
+
+        assert_accounts::<amm::instruction::QuoteSwap>(&quote_swap(&keys, 1_000, true), &quote);
+        assert_accounts::<amm::instruction::QuoteAddLiquidity>(
+            &quote_add_liquidity(&keys, 1_000, 2_000),
+            &quote,
+        );
+        assert_accounts::<amm::instruction::QuoteRemoveLiquidity>(
+            &quote_remove_liquidity(&keys, 1_000),
+            &quote,
+        );
+        assert_accounts::<amm::instruction::QuoteSpotPrice>(
+            &quote_spot_price(&keys),
+            &[
+                (keys.token_a_mint, ""),
+                (keys.token_b_mint, ""),
+                (keys.pool, ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn sync_writes_only_the_pool() {
+        let keys = pool_keys(CurveType::ConstantProduct);
+
+        assert_accounts::<amm::instruction::Sync>(
+            &sync(&keys),
+            &[
+                (keys.token_a_mint, ""),
+                (keys.token_b_mint, ""),
+                (keys.pool, "W"),
+                (keys.vault_a, ""),
+                (keys.vault_b, ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn skim_pays_the_recipient_token_accounts() {
+        let skimmer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let keys = pool_keys(CurveType::ConstantProduct);
+
+        assert_accounts::<amm::instruction::Skim>(
+            &skim(&skimmer, &keys, &recipient),
+            &[
+                (skimmer, "S"),
+                (keys.token_a_mint, ""),
+                (keys.token_b_mint, ""),
+                (keys.pool, ""),
+                (keys.vault_a, "W"),
+                (keys.vault_b, "W"),
+                (
+                    keys.associated_token_address(&recipient, &keys.token_a_mint),
+                    "W",
+                ),
+                (
+                    keys.associated_token_address(&recipient, &keys.token_b_mint),
+                    "W",
+                ),
+                (keys.token_program, ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn collect_protocol_fees_pays_the_treasury_token_accounts() {
+        let collector = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+        let keys = pool_keys(CurveType::ConstantProduct);
+
+        assert_accounts::<amm::instruction::CollectProtocolFees>(
+            &collect_protocol_fees(&collector, &keys, &treasury),
+            &[
+                (collector, "SW"),
+                (keys.config, ""),
+                (keys.token_a_mint, ""),
+                (keys.token_b_mint, ""),
+                (keys.pool, "W"),
+                (keys.vault_a, "W"),
+                (keys.vault_b, "W"),
+                (treasury, ""),
+                (
+                    keys.associated_token_address(&treasury, &keys.token_a_mint),
+                    "W",
+                ),
+                (
+                    keys.associated_token_address(&treasury, &keys.token_b_mint),
+                    "W",
+                ),
+                (system_program::ID, ""),
+                (keys.token_program, ""),
+                (associated_token::ID, ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn update_config_is_signed_by_the_admin() {
+        let admin = Pubkey::new_unique();
+
+        let ix = update_config(&admin, Some([1, 5, 30, 100]), None, None, None);
+        assert_accounts::<amm::instruction::UpdateConfig>(
+            &ix,
+            &[(admin, "S"), (config_address().0, "W")],
+        );
+    }
+
+    #[test]
+    fn status_changes_write_the_pool_or_the_config() {
+        let authority = Pubkey::new_unique();
+        let keys = pool_keys(CurveType::Weighted);
+
+        assert_accounts::<amm::instruction::SetPoolStatus>(
+            &set_pool_status(&authority, &keys, 1),
+            &[(authority, "S"), (keys.config, ""), (keys.pool, "W")],
+        );
+        assert_accounts::<amm::instruction::SetGlobalStatus>(
+            &set_global_status(&authority, 1),
+            &[(authority, "S"), (config_address().0, "W")],
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Rust client for the amm program.
+//!
+//! Builds on the program's `cpi` feature, so the instruction data and account
+//! lists always match the program they are sent to.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+
+pub use accounts::{decode_config, decode_pool, decode_position, decode_tick_array};
+pub use amm::state::{AmmConfig, CurveType, Pool, Position, Tick, TickArray};
+pub use amm::ID as PROGRAM_ID;
+pub use instructions::{PoolKeys, PositionKeys};
+pub use pda::canonical_pair;
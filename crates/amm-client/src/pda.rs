@@ -0,0 +1,89 @@
+use amm::state::CurveType;
+use anchor_lang::prelude::Pubkey;
+
+/// Orders two mints the way the program expects them, token A is always the lower pubkey
+///
+/// The returned flag is true when `mint_x` ended up as token A, which makes it the
+/// `a_to_b` direction of a swap selling `mint_x`.
+pub fn canonical_pair(mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, Pubkey, bool) {
+    if mint_x < mint_y {
+        (*mint_x, *mint_y, true)
+    } else {
+        (*mint_y, *mint_x, false)
+    }
+}
+
+/// Singleton `AmmConfig` account
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"amm_config"], &amm::ID)
+}
+
+/// Pool of a canonical pair for one fee tier and curve
+pub fn pool_address(
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    fee_bps: u16,
+    curve_type: CurveType,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"pool",
+            token_a_mint.as_ref(),
+            token_b_mint.as_ref(),
+            fee_bps.to_le_bytes().as_ref(),
+            &[curve_type as u8],
+        ],
+        &amm::ID,
+    )
+}
+
+/// Vault holding the pool's token A
+pub fn vault_a_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_a", pool.as_ref()], &amm::ID)
+}
+
+/// Vault holding the pool's token B
+pub fn vault_b_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_b", pool.as_ref()], &amm::ID)
+}
+
+/// LP mint of the pool
+pub fn lp_mint_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"lp_mint", pool.as_ref()], &amm::ID)
+}
+
+/// Token account holding the LP tokens locked on pool creation
+pub fn locked_lp_address(lp_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"locked_lp", lp_mint.as_ref()], &amm::ID)
+}
+
+/// Tick array of a concentrated liquidity pool starting at `start_tick_index`
+pub fn tick_array_address(pool: &Pubkey, start_tick_index: i32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"tick_array",
+            pool.as_ref(),
+            start_tick_index.to_le_bytes().as_ref(),
+        ],
+        &amm::ID,
+    )
+}
+
+/// Position of `owner` between two ticks of a concentrated liquidity pool
+pub fn position_address(
+    pool: &Pubkey,
+    owner: &Pubkey,
+    tick_lower: i32,
+    tick_upper: i32,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"position",
+            pool.as_ref(),
+            owner.as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref(),
+        ],
+        &amm::ID,
+    )
+}
@@ -0,0 +1,563 @@
+//! Concentrated liquidity (Uniswap v3) pricing for liquidity provided over price ranges
+//!
+//! Prices are quoted in token B per token A and tracked as their square root in Q64.64 fixed point. The price
+//! range is split in ticks, tick i being the price 1.0001^i. Liquidity L is constant between two initialized
+//! ticks, where the pool holds L / sqrt(P) of token A and L · sqrt(P) of token B like a constant product pool.
+
+use crate::error::{MathError, MathResult};
+use crate::fees::amount_after_fee;
+use crate::math::{SafeCast, SafeMath};
+use crate::u256::{CheckedU256, U256};
+use crate::BPS_DENOMINATOR;
+
+/// Bounds of the ticks, their square root prices are the bounds of a Q64.64 u128
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+
+/// Square root prices of MIN_TICK and MAX_TICK
+pub const MIN_SQRT_PRICE: u128 = 4_295_048_017;
+pub const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_062;
+
+const Q64_RESOLUTION: usize = 64;
+
+// 1 / sqrt(1.0001)^(2^i) in Q128.128 for every bit i of a tick up to 2^18
+const TICK_RATIOS: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+];
+
+// 2 / log2(1.0001) in Q32.32, turns the log2 of a square root price into its tick
+const TICKS_PER_LOG2_X32: i128 = 59_543_866_431_248;
+
+/// Square root price of `tick` in Q64.64, rounded up
+pub fn sqrt_price_at_tick(tick: i32) -> MathResult<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(MathError::InvalidInput);
+    }
+
+    // Product of the ratios of the bits set in |tick|, giving 1.0001^(-|tick| / 2) in Q128.128
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = match abs_tick & 1 {
+        0 => U256::one() << 128,
+        _ => U256::from(TICK_RATIOS[0]),
+    };
+    for (bit, tick_ratio) in TICK_RATIOS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = ratio.mul(U256::from(*tick_ratio))? >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 down to Q64.64
+    let round_up = !(ratio % (U256::one() << Q64_RESOLUTION)).is_zero();
+    let sqrt_price = (ratio >> Q64_RESOLUTION).to_u128()?;
+    Ok(sqrt_price + round_up as u128)
+}
+
+/// Largest tick with a square root price at or below `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> MathResult<i32> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+        return Err(MathError::InvalidInput);
+    }
+
+    // Integer part of log2(sqrt_price) from its most significant bit
+    let msb = 127 - sqrt_price.leading_zeros() as i128;
+    let mut log2_x32 = (msb - Q64_RESOLUTION as i128) << 32;
+
+    // 14 bits of the fractional part by squaring the price normalized to [1, 2) in Q1.63
+    let mut normalized = match msb >= 63 {
+        true => sqrt_price >> (msb - 63),
+        false => sqrt_price << (63 - msb),
+    };
+    for bit in (18..32).rev() {
+        let squared = normalized * normalized;
+        let above_two = (squared >> 127) as u32;
+        normalized = squared >> (63 + above_two);
+        log2_x32 += (above_two as i128) << bit;
+    }
+
+    // The estimate is within a tick of the exact one, which then only takes a couple of steps to reach
+    let mut tick =
+        ((log2_x32 * TICKS_PER_LOG2_X32) >> 64).clamp(MIN_TICK as i128, MAX_TICK as i128) as i32;
+    while tick > MIN_TICK && sqrt_price_at_tick(tick)? > sqrt_price {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_price_at_tick(tick + 1)? <= sqrt_price {
+        tick += 1;
+    }
+    Ok(tick)
+}
+
+fn ordered(sqrt_price_0: u128, sqrt_price_1: u128) -> (u128, u128) {
+    match sqrt_price_0 <= sqrt_price_1 {
+        true => (sqrt_price_0, sqrt_price_1),
+        false => (sqrt_price_1, sqrt_price_0),
+    }
+}
+
+fn amount_a_delta_u256(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<U256> {
+    let (lower, upper) = ordered(sqrt_price_0, sqrt_price_1);
+    if lower == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+
+    // L · (sqrt(P_upper) - sqrt(P_lower)) / (sqrt(P_upper) · sqrt(P_lower)), in one division
+    let numerator = (U256::from(liquidity) << Q64_RESOLUTION).mul(U256::from(upper - lower))?;
+    let denominator = U256::from(upper).mul(U256::from(lower))?;
+    match round_up {
+        true => numerator.div_ceil(denominator),
+        false => numerator.div(denominator),
+    }
+}
+
+fn amount_b_delta_u256(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<U256> {
+    let (lower, upper) = ordered(sqrt_price_0, sqrt_price_1);
+
+    // L · (sqrt(P_upper) - sqrt(P_lower))
+    let product = U256::from(liquidity).mul(U256::from(upper - lower))?;
+    match round_up {
+        true => product.div_ceil(U256::one() << Q64_RESOLUTION),
+        false => Ok(product >> Q64_RESOLUTION),
+    }
+}
+
+/// Amount of token A held by `liquidity` between two square root prices
+pub fn amount_a_delta(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<u64> {
+    amount_a_delta_u256(sqrt_price_0, sqrt_price_1, liquidity, round_up)?
+        .to_u128()?
+        .to_u64()
+}
+
+/// Amount of token B held by `liquidity` between two square root prices
+pub fn amount_b_delta(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<u64> {
+    amount_b_delta_u256(sqrt_price_0, sqrt_price_1, liquidity, round_up)?
+        .to_u128()?
+        .to_u64()
+}
+
+/// Square root price after `amount_in` of token A (`a_to_b`) or token B comes in, rounded against the swapper
+pub fn next_sqrt_price_from_input(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount_in: u64,
+    a_to_b: bool,
+) -> MathResult<u128> {
+    if sqrt_price == 0 || liquidity == 0 {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_in == 0 {
+        return Ok(sqrt_price);
+    }
+
+    let next = match a_to_b {
+        // L / (L / sqrt(P) + amount), rounded up so less goes out
+        true => {
+            let numerator = U256::from(liquidity) << Q64_RESOLUTION;
+            let denominator = numerator
+                .div(U256::from(sqrt_price))?
+                .add(U256::from(amount_in))?;
+            numerator.div_ceil(denominator)?
+        }
+        // sqrt(P) + amount / L, rounded down so less goes out
+        false => {
+            let shift = (U256::from(amount_in) << Q64_RESOLUTION).div(U256::from(liquidity))?;
+            U256::from(sqrt_price).add(shift)?
+        }
+    };
+    next.to_u128()
+}
+
+/// Part of a swap within a range of constant liquidity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    /// Square root price the step ends at, the target if it was reached
+    pub sqrt_price_next: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Swap fee charged on top of amount_in
+    pub fee_amount: u64,
+}
+
+/// Exact input swap of up to `amount_remaining` (fee included) from `sqrt_price` toward `sqrt_price_target`
+///
+/// The direction follows the target, a lower price sells token A. The input rounds up and the output down.
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_bps: u16,
+) -> MathResult<SwapStep> {
+    let a_to_b = sqrt_price_target <= sqrt_price;
+    let amount_in_delta = |to: u128, round_up: bool| match a_to_b {
+        true => amount_a_delta_u256(to, sqrt_price, liquidity, round_up),
+        false => amount_b_delta_u256(sqrt_price, to, liquidity, round_up),
+    };
+
+    // The target is reached if what is left after the fee covers the input up to it
+    let amount_remaining_less_fee = amount_after_fee(amount_remaining, fee_bps)?;
+    let amount_in_to_target = amount_in_delta(sqrt_price_target, true)?;
+    let sqrt_price_next = match amount_in_to_target <= U256::from(amount_remaining_less_fee) {
+        true => sqrt_price_target,
+        false => {
+            next_sqrt_price_from_input(sqrt_price, liquidity, amount_remaining_less_fee, a_to_b)?
+        }
+    };
+    let reached_target = sqrt_price_next == sqrt_price_target;
+
+    let amount_in = match reached_target {
+        true => amount_in_to_target,
+        false => amount_in_delta(sqrt_price_next, true)?,
+    }
+    .to_u128()?
+    .to_u64()?;
+    let amount_out = match a_to_b {
+        true => amount_b_delta(sqrt_price_next, sqrt_price, liquidity, false)?,
+        false => amount_a_delta(sqrt_price, sqrt_price_next, liquidity, false)?,
+    };
+
+    // Short of the target everything left goes in, the rounding included in the fee
+    let fee_amount = match reached_target {
+        true => (amount_in as u128)
+            .safe_mul(fee_bps as u128)?
+            .safe_add(BPS_DENOMINATOR.safe_sub(fee_bps as u64)? as u128 - 1)?
+            .safe_div(BPS_DENOMINATOR.safe_sub(fee_bps as u64)? as u128)?
+            .to_u64()?,
+        false => amount_remaining.safe_sub(amount_in)?,
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// Price of token A in token B (Q64.64) at `sqrt_price`, rounded down
+pub fn concentrated_spot_price(sqrt_price: u128) -> MathResult<u128> {
+    (U256::from(sqrt_price).mul(U256::from(sqrt_price))? >> Q64_RESOLUTION).to_u128()
+}
+
+/// Amounts of token A and token B held by `liquidity` over a range at the current square root price
+pub fn amounts_for_liquidity(
+    sqrt_price: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<(u64, u64)> {
+    if sqrt_price_lower >= sqrt_price_upper {
+        return Err(MathError::InvalidInput);
+    }
+
+    // All in token A below the range, all in token B above it
+    if sqrt_price <= sqrt_price_lower {
+        let amount_a = amount_a_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?;
+        Ok((amount_a, 0))
+    } else if sqrt_price >= sqrt_price_upper {
+        let amount_b = amount_b_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?;
+        Ok((0, amount_b))
+    } else {
+        let amount_a = amount_a_delta(sqrt_price, sqrt_price_upper, liquidity, round_up)?;
+        let amount_b = amount_b_delta(sqrt_price_lower, sqrt_price, liquidity, round_up)?;
+        Ok((amount_a, amount_b))
+    }
+}
+
+/// Most liquidity over a range `amount_a` and `amount_b` can provide at the current square root price, rounded down
+pub fn liquidity_for_amounts(
+    sqrt_price: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    amount_a: u64,
+    amount_b: u64,
+) -> MathResult<u128> {
+    if sqrt_price_lower == 0 || sqrt_price_lower >= sqrt_price_upper {
+        return Err(MathError::InvalidInput);
+    }
+
+    // L = amount_a · sqrt(P_a) · sqrt(P_b) / (sqrt(P_b) - sqrt(P_a))
+    let liquidity_a = |lower: u128, upper: u128| -> MathResult<u128> {
+        U256::from(amount_a)
+            .mul(U256::from(lower))?
+            .mul(U256::from(upper))?
+            .div(U256::from(upper - lower) << Q64_RESOLUTION)?
+            .to_u128()
+    };
+    // L = amount_b / (sqrt(P_b) - sqrt(P_a))
+    let liquidity_b = |lower: u128, upper: u128| -> MathResult<u128> {
+        (U256::from(amount_b) << Q64_RESOLUTION)
+            .div(U256::from(upper - lower))?
+            .to_u128()
+    };
+
+    if sqrt_price <= sqrt_price_lower {
+        liquidity_a(sqrt_price_lower, sqrt_price_upper)
+    } else if sqrt_price >= sqrt_price_upper {
+        liquidity_b(sqrt_price_lower, sqrt_price_upper)
+    } else {
+        Ok(liquidity_a(sqrt_price, sqrt_price_upper)?
+            .min(liquidity_b(sqrt_price_lower, sqrt_price)?))
+    }
+}
+
+/// Liquidity after adding a signed `delta` to it
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> MathResult<u128> {
+    match delta >= 0 {
+        true => liquidity.safe_add(delta.unsigned_abs()),
+        false => liquidity.safe_sub(delta.unsigned_abs()),
+    }
+}
+
+/// Fee growth per unit of liquidity (Q64.64) of a `fee_amount` shared by `liquidity`, rounded down
+pub fn fee_growth_delta(fee_amount: u64, liquidity: u128) -> MathResult<u128> {
+    ((fee_amount as u128) << Q64_RESOLUTION).safe_div(liquidity)
+}
+
+/// Fee growth per unit of liquidity inside a range, from the fee growth outside of both of its ticks
+///
+/// Fee growths only ever increase and are compared through their differences, they are free to wrap around.
+pub fn fee_growth_inside(
+    tick_current: i32,
+    tick_lower: i32,
+    fee_growth_outside_lower: u128,
+    tick_upper: i32,
+    fee_growth_outside_upper: u128,
+    fee_growth_global: u128,
+) -> u128 {
+    let below = match tick_current >= tick_lower {
+        true => fee_growth_outside_lower,
+        false => fee_growth_global.wrapping_sub(fee_growth_outside_lower),
+    };
+    let above = match tick_current < tick_upper {
+        true => fee_growth_outside_upper,
+        false => fee_growth_global.wrapping_sub(fee_growth_outside_upper),
+    };
+    fee_growth_global.wrapping_sub(below).wrapping_sub(above)
+}
+
+/// Fees earned by `liquidity` since the fee growth inside its range was `fee_growth_inside_last`, rounded down
+pub fn fees_owed(
+    fee_growth_inside: u128,
+    fee_growth_inside_last: u128,
+    liquidity: u128,
+) -> MathResult<u64> {
+    let growth = fee_growth_inside.wrapping_sub(fee_growth_inside_last);
+    (U256::from(growth).mul(U256::from(liquidity))? >> Q64_RESOLUTION)
+        .to_u128()?
+        .to_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q64: u128 = 1 << 64;
+
+    #[test]
+    fn tick_bounds_match_the_sqrt_price_bounds() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), Q64);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE);
+        assert_eq!(
+            sqrt_price_at_tick(MIN_TICK - 1),
+            Err(MathError::InvalidInput)
+        );
+        assert_eq!(
+            sqrt_price_at_tick(MAX_TICK + 1),
+            Err(MathError::InvalidInput)
+        );
+
+        assert_eq!(tick_at_sqrt_price(MIN_SQRT_PRICE).unwrap(), MIN_TICK);
+        assert_eq!(tick_at_sqrt_price(MAX_SQRT_PRICE).unwrap(), MAX_TICK);
+        assert_eq!(
+            tick_at_sqrt_price(MIN_SQRT_PRICE - 1),
+            Err(MathError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn sqrt_price_follows_one_basis_point_per_tick() {
+        // sqrt(1.0001^100) = 1.00501...
+        let sqrt_price = sqrt_price_at_tick(100).unwrap();
+        let expected = (1.0001f64.powi(100).sqrt() * Q64 as f64) as u128;
+        assert!(sqrt_price.abs_diff(expected) < Q64 / 1_000_000_000_000);
+
+        let inverse = sqrt_price_at_tick(-100).unwrap();
+        let expected = (1.0001f64.powi(-100).sqrt() * Q64 as f64) as u128;
+        assert!(inverse.abs_diff(expected) < Q64 / 1_000_000_000_000);
+
+        assert_eq!(concentrated_spot_price(Q64).unwrap(), Q64);
+        assert_eq!(concentrated_spot_price(2 * Q64).unwrap(), 4 * Q64);
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_inverts_sqrt_price_at_tick() {
+        for tick in [
+            MIN_TICK + 1,
+            -300_000,
+            -1_001,
+            -1,
+            0,
+            1,
+            64,
+            12_345,
+            300_000,
+            MAX_TICK - 1,
+        ] {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+            assert_eq!(tick_at_sqrt_price(sqrt_price + 1).unwrap(), tick);
+            assert_eq!(tick_at_sqrt_price(sqrt_price - 1).unwrap(), tick - 1);
+        }
+    }
+
+    #[test]
+    fn amounts_split_around_the_current_price() {
+        let (lower, upper) = (
+            sqrt_price_at_tick(-1_000).unwrap(),
+            sqrt_price_at_tick(1_000).unwrap(),
+        );
+        let liquidity = 1_000_000_000u128;
+
+        // Only token A below the range, only token B above it, both inside
+        let (a, b) = amounts_for_liquidity(lower - 1, lower, upper, liquidity, false).unwrap();
+        assert!(a > 0 && b == 0);
+        let (a, b) = amounts_for_liquidity(upper, lower, upper, liquidity, false).unwrap();
+        assert!(a == 0 && b > 0);
+        let (a, b) = amounts_for_liquidity(Q64, lower, upper, liquidity, false).unwrap();
+        assert_eq!((a, b), (48_768_197, 48_768_197));
+
+        // Rounding up never asks for less
+        let (a_up, b_up) = amounts_for_liquidity(Q64, lower, upper, liquidity, true).unwrap();
+        assert_eq!((a_up, b_up), (a + 1, b + 1));
+
+        // The liquidity the amounts can provide gives them back
+        let provided = liquidity_for_amounts(Q64, lower, upper, a_up, b_up).unwrap();
+        assert!(provided >= liquidity);
+    }
+
+    #[test]
+    fn swap_step_stops_at_the_target() {
+        let target = sqrt_price_at_tick(-10).unwrap();
+        let liquidity = 1_000_000_000_000u128;
+
+        let step = compute_swap_step(Q64, target, liquidity, u64::MAX / 2, 30).unwrap();
+        assert_eq!(step.sqrt_price_next, target);
+        assert_eq!(
+            step.amount_in,
+            amount_a_delta(target, Q64, liquidity, true).unwrap()
+        );
+        assert_eq!(
+            step.amount_out,
+            amount_b_delta(target, Q64, liquidity, false).unwrap()
+        );
+        assert!(step.amount_out < step.amount_in);
+        assert_eq!(step.fee_amount, (step.amount_in * 30).div_ceil(9_970));
+    }
+
+    #[test]
+    fn swap_step_uses_everything_short_of_the_target() {
+        let target = sqrt_price_at_tick(1_000).unwrap();
+        let liquidity = 1_000_000_000_000u128;
+
+        let step = compute_swap_step(Q64, target, liquidity, 1_000_000, 30).unwrap();
+        assert!(step.sqrt_price_next > Q64 && step.sqrt_price_next < target);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000_000);
+        assert!(step.fee_amount >= 3_000);
+        assert!(step.amount_out < step.amount_in);
+    }
+
+    #[test]
+    fn empty_ranges_are_skipped() {
+        let target = sqrt_price_at_tick(-500).unwrap();
+        let step = compute_swap_step(Q64, target, 0, 1_000, 30).unwrap();
+        assert_eq!(
+            step,
+            SwapStep {
+                sqrt_price_next: target,
+                amount_in: 0,
+                amount_out: 0,
+                fee_amount: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn fee_growth_inside_depends_on_the_current_tick() {
+        // 100 of growth below the range, 30 above it and 70 inside, out of 200
+        let (lower, upper, global) = (-100, 100, 200u128);
+        assert_eq!(fee_growth_inside(0, lower, 100, upper, 30, global), 70);
+        assert_eq!(
+            fee_growth_inside(-200, lower, global - 100, upper, 30, global),
+            70
+        );
+        assert_eq!(
+            fee_growth_inside(200, lower, 100, upper, global - 30, global),
+            70
+        );
+
+        // Wrapping around doesn't change the differences
+        let wrapped = fee_growth_inside(
+            0,
+            lower,
+            100u128.wrapping_sub(1_000),
+            upper,
+            30,
+            global.wrapping_sub(1_000),
+        );
+        assert_eq!(wrapped, 70);
+        assert_eq!(
+            fees_owed(wrapped.wrapping_add(Q64), wrapped, 1_000).unwrap(),
+            1_000
+        );
+    }
+
+    #[test]
+    fn liquidity_deltas_are_signed() {
+        assert_eq!(add_liquidity_delta(100, 50).unwrap(), 150);
+        assert_eq!(add_liquidity_delta(100, -50).unwrap(), 50);
+        assert_eq!(add_liquidity_delta(100, -101), Err(MathError::Overflow));
+        assert_eq!(fee_growth_delta(1, 2).unwrap(), Q64 / 2);
+    }
+}
@@ -0,0 +1,228 @@
+//! Constant product (x * y = k) pricing and LP share math
+
+use core::cmp;
+
+use crate::error::{MathError, MathResult};
+use crate::fees::amount_after_fee;
+use crate::math::{sqrt_u128, SafeCast, SafeMath};
+use crate::BPS_DENOMINATOR;
+
+/// Token amounts pulled from a depositor and the LP tokens minted for them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Deposit {
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_minted: u64,
+}
+
+/// Token amounts paid out for burned LP tokens
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+/// Output of an exact input swap of `amount_in`, after the pool's fee and rounded down
+pub fn swap_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee_bps: u16,
+) -> MathResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_in == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    // Taking the pool's fee for liquidity providers
+    let amount_in_with_fee = amount_after_fee(amount_in, fee_bps)? as u128;
+
+    // k = x * y, the reserve left is rounded up so k never decreases
+    let k = (reserve_in as u128).safe_mul(reserve_out as u128)?;
+    let new_reserve_in = (reserve_in as u128).safe_add(amount_in_with_fee)?;
+    let new_reserve_out = k.safe_div_ceil(new_reserve_in)?;
+
+    (reserve_out as u128).safe_sub(new_reserve_out)?.to_u64()
+}
+
+/// Input needed for an exact output swap of `amount_out`, fee included and rounded up
+pub fn swap_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee_bps: u16,
+) -> MathResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 || amount_out >= reserve_out {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_out == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    // x * y = k  =>  amount_in_with_fee = x * amount_out / (y - amount_out)
+    let numerator = (reserve_in as u128).safe_mul(amount_out as u128)?;
+    let denominator = (reserve_out as u128).safe_sub(amount_out as u128)?;
+    let amount_in_with_fee = numerator.safe_div_ceil(denominator)?;
+
+    // Adding the pool's fee for liquidity providers on top
+    let fee_denominator = BPS_DENOMINATOR.safe_sub(fee_bps as u64)? as u128;
+    amount_in_with_fee
+        .safe_mul(BPS_DENOMINATOR as u128)?
+        .safe_div_ceil(fee_denominator)?
+        .to_u64()
+}
+
+/// LP supply minted for the first deposit of a pool: sqrt(a * b), rounded down
+pub fn initial_liquidity(amount_a: u64, amount_b: u64) -> MathResult<u64> {
+    sqrt_u128((amount_a as u128).safe_mul(amount_b as u128)?).to_u64()
+}
+
+/// Deposit at the pool's current ratio, using as much of `amount_a` and `amount_b` as fits
+///
+/// LP tokens are rounded down and the amounts pulled up, both in favor of the pool.
+pub fn deposit_shares(
+    amount_a: u64,
+    amount_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+) -> MathResult<Deposit> {
+    // A pool without liquidity has no share price
+    if lp_supply == 0 || reserve_a == 0 || reserve_b == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    // lp_tokens = amount_provided / reserve * lp_supply, the scarcer side decides
+    let lp_for_a = (amount_a as u128)
+        .safe_mul(lp_supply as u128)?
+        .safe_div(reserve_a as u128)?;
+    let lp_for_b = (amount_b as u128)
+        .safe_mul(lp_supply as u128)?
+        .safe_div(reserve_b as u128)?;
+    let lp_minted = cmp::min(lp_for_a, lp_for_b);
+
+    // Amounts backing the LP tokens minted, never more than provided since lp_minted was rounded down
+    let deposit_a = lp_minted
+        .safe_mul(reserve_a as u128)?
+        .safe_div_ceil(lp_supply as u128)?
+        .to_u64()?;
+    let deposit_b = lp_minted
+        .safe_mul(reserve_b as u128)?
+        .safe_div_ceil(lp_supply as u128)?
+        .to_u64()?;
+
+    if amount_a < deposit_a || amount_b < deposit_b {
+        return Err(MathError::InsufficientAmount);
+    }
+
+    Ok(Deposit {
+        amount_a: deposit_a,
+        amount_b: deposit_b,
+        lp_minted: lp_minted.to_u64()?,
+    })
+}
+
+/// Share of the reserves owed for burning `lp_amount`, rounded down in favor of the pool
+pub fn withdrawal_amounts(
+    lp_amount: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+) -> MathResult<Withdrawal> {
+    if lp_supply == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    // amount = lp_amount / lp_supply * reserve
+    let amount_a = (lp_amount as u128)
+        .safe_mul(reserve_a as u128)?
+        .safe_div(lp_supply as u128)?
+        .to_u64()?;
+    let amount_b = (lp_amount as u128)
+        .safe_mul(reserve_b as u128)?
+        .safe_div(lp_supply as u128)?
+        .to_u64()?;
+
+    Ok(Withdrawal { amount_a, amount_b })
+}
+
+/// Marginal price of token A in token B (Q64.64): reserve_b / reserve_a, rounded down
+pub fn spot_price(reserve_a: u64, reserve_b: u64) -> MathResult<u128> {
+    if reserve_a == 0 || reserve_b == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    ((reserve_b as u128) << 64).safe_div(reserve_a as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_exact_in_takes_fee_and_keeps_k() {
+        let out = swap_exact_in(1_000_000, 1_000_000, 10_000, 30).unwrap();
+        // 9_970 after fee => 1e12 / 1_009_970 = 990_128.4, rounded up to 990_129 left in the pool
+        assert_eq!(out, 9_871);
+        assert!((1_000_000u128 + 9_970) * (1_000_000 - out as u128) >= 1_000_000u128 * 1_000_000);
+
+        assert_eq!(
+            swap_exact_in(0, 1, 1, 30),
+            Err(MathError::InsufficientLiquidity)
+        );
+        assert_eq!(swap_exact_in(1, 1, 0, 30), Err(MathError::InvalidInput));
+    }
+
+    #[test]
+    fn swap_exact_out_covers_the_requested_output() {
+        let amount_in = swap_exact_out(1_000_000, 1_000_000, 9_871, 30).unwrap();
+        assert!(swap_exact_in(1_000_000, 1_000_000, amount_in, 30).unwrap() >= 9_871);
+
+        assert_eq!(
+            swap_exact_out(1_000_000, 1_000_000, 1_000_000, 30),
+            Err(MathError::InsufficientLiquidity)
+        );
+        assert_eq!(swap_exact_out(1, 2, 0, 30), Err(MathError::InvalidInput));
+    }
+
+    #[test]
+    fn deposit_and_withdrawal_round_in_favor_of_the_pool() {
+        let deposit = deposit_shares(1_000, 3_000, 10_000, 20_000, 5_000).unwrap();
+        assert_eq!(
+            deposit,
+            Deposit {
+                amount_a: 1_000,
+                amount_b: 2_000,
+                lp_minted: 500,
+            }
+        );
+
+        let withdrawal = withdrawal_amounts(333, 10_001, 20_001, 5_000).unwrap();
+        assert_eq!(
+            withdrawal,
+            Withdrawal {
+                amount_a: 666,
+                amount_b: 1_332,
+            }
+        );
+
+        assert_eq!(deposit_shares(1, 1, 0, 1, 1), Err(MathError::EmptyPool));
+        assert_eq!(withdrawal_amounts(1, 1, 1, 0), Err(MathError::EmptyPool));
+    }
+
+    #[test]
+    fn initial_liquidity_is_the_geometric_mean() {
+        assert_eq!(initial_liquidity(4_000_000, 1_000_000).unwrap(), 2_000_000);
+        assert_eq!(initial_liquidity(u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(initial_liquidity(0, 5).unwrap(), 0);
+    }
+
+    #[test]
+    fn spot_price_is_the_reserve_ratio() {
+        assert_eq!(spot_price(1_000_000, 1_000_000).unwrap(), 1 << 64);
+        assert_eq!(spot_price(1_000_000, 2_500_000).unwrap(), 5 << 63);
+        assert_eq!(spot_price(0, 1_000_000), Err(MathError::EmptyPool));
+    }
+}
@@ -0,0 +1,40 @@
+use core::fmt;
+
+/// Failures of the pool math, the program maps each of them to an `AMMError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// An intermediate or final value does not fit its integer type
+    Overflow,
+    /// A division by zero
+    DivisionByZero,
+    /// The pool has no liquidity (zero LP supply or zero reserves)
+    EmptyPool,
+    /// The reserves can't cover the requested trade
+    InsufficientLiquidity,
+    /// A zero or out of range amount
+    InvalidInput,
+    /// The amounts provided don't cover the deposit
+    InsufficientAmount,
+    /// An iterative solver of the curve did not converge
+    NoConvergence,
+    /// The swap is too large a share of the reserves for the curve
+    MaxRatioExceeded,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            MathError::Overflow => "math operation overflowed",
+            MathError::DivisionByZero => "math operation divided by zero",
+            MathError::EmptyPool => "the pool has no liquidity",
+            MathError::InsufficientLiquidity => "the pool does not have enough liquidity",
+            MathError::InvalidInput => "the amount provided is not valid",
+            MathError::InsufficientAmount => "the amount provided is insufficient",
+            MathError::NoConvergence => "the curve invariant did not converge",
+            MathError::MaxRatioExceeded => "the swap is too large a share of the reserves",
+        };
+        f.write_str(msg)
+    }
+}
+
+pub type MathResult<T> = Result<T, MathError>;
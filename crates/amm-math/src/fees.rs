@@ -0,0 +1,24 @@
+use crate::error::MathResult;
+use crate::math::{SafeCast, SafeMath};
+use crate::BPS_DENOMINATOR;
+
+/// Amount left to trade with once the swap fee is taken, rounded down
+pub fn amount_after_fee(amount_in: u64, fee_bps: u16) -> MathResult<u64> {
+    (amount_in as u128)
+        .safe_mul(BPS_DENOMINATOR.safe_sub(fee_bps as u64)? as u128)?
+        .safe_div(BPS_DENOMINATOR as u128)?
+        .to_u64()
+}
+
+/// Swap fee taken from `amount_in`, what `amount_after_fee` leaves out
+pub fn swap_fee(amount_in: u64, fee_bps: u16) -> MathResult<u64> {
+    amount_in.safe_sub(amount_after_fee(amount_in, fee_bps)?)
+}
+
+/// Share of the swap fee owed to the protocol, rounded down in favor of the liquidity providers
+pub fn protocol_fee(swap_fee: u64, protocol_fee_bps: u16) -> MathResult<u64> {
+    (swap_fee as u128)
+        .safe_mul(protocol_fee_bps as u128)?
+        .safe_div(BPS_DENOMINATOR as u128)?
+        .to_u64()
+}
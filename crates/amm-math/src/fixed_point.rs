@@ -0,0 +1,182 @@
+//! 18 decimal fixed point `ln`, `exp` and `pow` in integer math, for the weighted pools
+//!
+//! Values are `u128` scaled by `ONE`, logarithms are signed. Each result is within a relative
+//! `MAX_POW_RELATIVE_ERROR` of the exact one, `pow_down` and `pow_up` move it by that much so the
+//! rounding can always go in favor of the pool.
+
+use crate::error::{MathError, MathResult};
+use crate::math::SafeMath;
+
+/// 1.0 in fixed point
+pub const ONE: u128 = 1_000_000_000_000_000_000;
+
+/// Bound on the relative error of `pow`, 1e-14
+pub const MAX_POW_RELATIVE_ERROR: u128 = 10_000;
+
+const ONE_I: i128 = ONE as i128;
+const LN_2: i128 = 693_147_180_559_945_309;
+
+// Below this exp rounds to 0, above it the result doesn't fit in a u128 anymore
+const MIN_EXP: i128 = -42 * ONE_I;
+const MAX_EXP: i128 = 46 * ONE_I;
+
+/// `a * b`, rounded down
+pub fn mul_down(a: u128, b: u128) -> MathResult<u128> {
+    a.safe_mul(b)?.safe_div(ONE)
+}
+
+/// `a * b`, rounded up
+pub fn mul_up(a: u128, b: u128) -> MathResult<u128> {
+    a.safe_mul(b)?.safe_div_ceil(ONE)
+}
+
+/// `a / b`, rounded down
+pub fn div_down(a: u128, b: u128) -> MathResult<u128> {
+    a.safe_mul(ONE)?.safe_div(b)
+}
+
+/// `a / b`, rounded up
+pub fn div_up(a: u128, b: u128) -> MathResult<u128> {
+    a.safe_mul(ONE)?.safe_div_ceil(b)
+}
+
+/// Natural logarithm of `x`
+pub fn ln(x: u128) -> MathResult<i128> {
+    if x == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    // x = m * 2^k with m in [1, 2), the shift right drops at most one unit of m
+    let (mut m, mut k) = (x, 0i128);
+    if m >= 2 * ONE {
+        k = (127 - (m / ONE).leading_zeros()) as i128;
+        m >>= k;
+    }
+    while m < ONE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...), with z = (m - 1) / (m + 1) in [0, 1/3)
+    let m = m as i128;
+    let z = (m - ONE_I) * ONE_I / (m + ONE_I);
+    let z_squared = z * z / ONE_I;
+    let (mut term, mut sum, mut n) = (z, 0i128, 1i128);
+    while term != 0 {
+        sum += term / n;
+        term = term * z_squared / ONE_I;
+        n += 2;
+    }
+
+    Ok(2 * sum + k * LN_2)
+}
+
+/// e to the power of `x`
+pub fn exp(x: i128) -> MathResult<u128> {
+    if x < MIN_EXP {
+        return Ok(0);
+    }
+    if x > MAX_EXP {
+        return Err(MathError::Overflow);
+    }
+
+    // e^x = 2^k * e^r with r in [-ln(2)/2, ln(2)/2]
+    let k = (x + x.signum() * LN_2 / 2) / LN_2;
+    let r = x - k * LN_2;
+
+    // e^r = 1 + r + r^2/2! + r^3/3! + ...
+    let (mut term, mut sum, mut n) = (ONE_I, ONE_I, 1i128);
+    while term != 0 {
+        term = term * r / ONE_I / n;
+        sum += term;
+        n += 1;
+    }
+
+    let sum = sum as u128;
+    Ok(match k >= 0 {
+        true => sum << k,
+        false => sum >> -k,
+    })
+}
+
+/// `x` to the power of `y`, both in fixed point
+pub fn pow(x: u128, y: u128) -> MathResult<u128> {
+    if y == 0 || x == ONE {
+        return Ok(ONE);
+    }
+    if x == 0 {
+        return Ok(0);
+    }
+
+    // ln(x) * y split on the integer part of y, so the product can't overflow
+    let ln_x = ln(x)?;
+    let (y_int, y_frac) = ((y / ONE) as i128, (y % ONE) as i128);
+    let exponent = ln_x
+        .checked_mul(y_int)
+        .and_then(|whole| whole.checked_add(ln_x.checked_mul(y_frac)? / ONE_I))
+        .ok_or(MathError::Overflow)?;
+
+    exp(exponent)
+}
+
+/// `pow`, moved down by its maximum error
+pub fn pow_down(x: u128, y: u128) -> MathResult<u128> {
+    minus_max_error(pow(x, y)?)
+}
+
+/// `pow`, moved up by its maximum error
+pub fn pow_up(x: u128, y: u128) -> MathResult<u128> {
+    plus_max_error(pow(x, y)?)
+}
+
+/// Lower bound of a value computed with `exp`
+pub fn minus_max_error(value: u128) -> MathResult<u128> {
+    let max_error = mul_up(value, MAX_POW_RELATIVE_ERROR)?.safe_add(1)?;
+    Ok(value.saturating_sub(max_error))
+}
+
+/// Upper bound of a value computed with `exp`
+pub fn plus_max_error(value: u128) -> MathResult<u128> {
+    let max_error = mul_up(value, MAX_POW_RELATIVE_ERROR)?.safe_add(1)?;
+    value.safe_add(max_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: u128, expected: f64) {
+        let expected = expected * ONE as f64;
+        let error = (actual as f64 - expected).abs() / expected;
+        assert!(error < 1e-15, "{actual} is not {expected}");
+    }
+
+    #[test]
+    fn ln_and_exp_invert_each_other() {
+        assert_eq!(ln(ONE).unwrap(), 0);
+        assert_eq!(exp(0).unwrap(), ONE);
+        assert_close(exp(ONE_I).unwrap(), core::f64::consts::E);
+        assert_close(exp(-3 * ONE_I).unwrap(), (-3f64).exp());
+        assert!((ln(2 * ONE).unwrap() - LN_2).abs() <= 1);
+        assert!((ln(ONE / 10).unwrap() - (-2_302_585_092_994_045_684)).abs() <= 10);
+        assert_eq!(ln(0), Err(MathError::InvalidInput));
+
+        assert_eq!(exp(-50 * ONE_I).unwrap(), 0);
+        assert_eq!(exp(50 * ONE_I), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn pow_matches_floats() {
+        assert_close(pow(2 * ONE, ONE / 2).unwrap(), core::f64::consts::SQRT_2);
+        assert_close(pow(ONE / 2, 4 * ONE).unwrap(), 0.0625);
+        assert_close(pow(7 * ONE / 10, 20 * ONE).unwrap(), 0.7f64.powi(20));
+        assert_close(
+            pow(u64::MAX as u128 * ONE, ONE / 5).unwrap(),
+            (u64::MAX as f64).powf(0.2),
+        );
+
+        let exact = pow(3 * ONE, ONE / 3).unwrap();
+        assert!(pow_down(3 * ONE, ONE / 3).unwrap() < exact);
+        assert!(pow_up(3 * ONE, ONE / 3).unwrap() > exact);
+    }
+}
@@ -0,0 +1,27 @@
+//! Pure, `no_std` math behind the amm program.
+//!
+//! Every function here is what the program runs on-chain, so off-chain
+//! clients get bit-exact quotes by calling the same code.
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod concentrated;
+pub mod constant_product;
+pub mod error;
+pub mod fees;
+pub mod fixed_point;
+pub mod math;
+pub mod stable_swap;
+mod u256;
+pub mod weighted;
+
+pub use concentrated::*;
+pub use constant_product::*;
+pub use error::MathError;
+pub use fees::*;
+pub use math::*;
+pub use stable_swap::*;
+pub use weighted::*;
+
+/// Denominator for every basis point value
+pub const BPS_DENOMINATOR: u64 = 10_000;
@@ -0,0 +1,162 @@
+use crate::error::{MathError, MathResult};
+
+/// Checked arithmetic that fails with a `MathError` instead of panicking
+pub trait SafeMath: Sized {
+    fn safe_add(self, rhs: Self) -> MathResult<Self>;
+    fn safe_sub(self, rhs: Self) -> MathResult<Self>;
+    fn safe_mul(self, rhs: Self) -> MathResult<Self>;
+    fn safe_div(self, rhs: Self) -> MathResult<Self>;
+    /// Division rounded up
+    fn safe_div_ceil(self, rhs: Self) -> MathResult<Self>;
+}
+
+macro_rules! impl_safe_math {
+    ($($t:ty),*) => {$(
+        impl SafeMath for $t {
+            fn safe_add(self, rhs: Self) -> MathResult<Self> {
+                self.checked_add(rhs).ok_or(MathError::Overflow)
+            }
+
+            fn safe_sub(self, rhs: Self) -> MathResult<Self> {
+                self.checked_sub(rhs).ok_or(MathError::Overflow)
+            }
+
+            fn safe_mul(self, rhs: Self) -> MathResult<Self> {
+                self.checked_mul(rhs).ok_or(MathError::Overflow)
+            }
+
+            fn safe_div(self, rhs: Self) -> MathResult<Self> {
+                self.checked_div(rhs).ok_or(MathError::DivisionByZero)
+            }
+
+            fn safe_div_ceil(self, rhs: Self) -> MathResult<Self> {
+                if rhs == 0 {
+                    return Err(MathError::DivisionByZero);
+                }
+                Ok(self.div_ceil(rhs))
+            }
+        }
+    )*};
+}
+
+impl_safe_math!(u64, u128);
+
+/// Narrowing cast that fails with `MathError::Overflow` instead of truncating
+pub trait SafeCast {
+    fn to_u64(self) -> MathResult<u64>;
+}
+
+impl SafeCast for u128 {
+    fn to_u64(self) -> MathResult<u64> {
+        u64::try_from(self).map_err(|_| MathError::Overflow)
+    }
+}
+
+/// Integer square root of a u128, rounded down
+///
+/// Newton's method starting from a power of two that is always >= sqrt(value),
+/// so the iterates decrease monotonically and it stops at floor(sqrt(value)).
+/// The result always fits in a u64.
+pub fn sqrt_u128(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    // 2^ceil(bits / 2) is the smallest power of two guaranteed to be >= sqrt(value)
+    let bits = 128 - value.leading_zeros();
+    let mut x0 = 1u128 << bits.div_ceil(2);
+
+    loop {
+        let x1 = (x0 + value / x0) >> 1;
+        if x1 >= x0 {
+            return x0;
+        }
+        x0 = x1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_math_maps_failures_to_errors() {
+        assert_eq!(2u64.safe_add(3), Ok(5));
+        assert_eq!(u64::MAX.safe_add(1), Err(MathError::Overflow));
+        assert_eq!(0u64.safe_sub(1), Err(MathError::Overflow));
+        assert_eq!(u128::MAX.safe_mul(2), Err(MathError::Overflow));
+        assert_eq!(10u128.safe_div(0), Err(MathError::DivisionByZero));
+        assert_eq!(10u128.safe_div_ceil(0), Err(MathError::DivisionByZero));
+        assert_eq!(10u128.safe_div(4), Ok(2));
+        assert_eq!(10u128.safe_div_ceil(4), Ok(3));
+        assert_eq!((u64::MAX as u128).to_u64(), Ok(u64::MAX));
+        assert_eq!((u64::MAX as u128 + 1).to_u64(), Err(MathError::Overflow));
+    }
+
+    fn assert_floor_sqrt(value: u128) {
+        let root = sqrt_u128(value);
+        assert!(root * root <= value, "sqrt({value}) = {root} is too large");
+        let next = root + 1;
+        assert!(
+            next.checked_mul(next).is_none_or(|square| square > value),
+            "sqrt({value}) = {root} is too small"
+        );
+    }
+
+    #[test]
+    fn sqrt_small_values_exhaustive() {
+        for value in 0..=1_000_000u128 {
+            assert_floor_sqrt(value);
+        }
+    }
+
+    #[test]
+    fn sqrt_perfect_squares_and_neighbours() {
+        for root in (0..=u64::MAX as u128).step_by(0x0000_1357_9bdf_0246_u128 as usize) {
+            let square = root * root;
+            assert_eq!(sqrt_u128(square), root);
+            if square > 0 {
+                assert_eq!(sqrt_u128(square - 1), root - 1);
+            }
+            assert_floor_sqrt(square + 1);
+        }
+
+        let max_root = u64::MAX as u128;
+        assert_eq!(sqrt_u128(max_root * max_root), max_root);
+        assert_eq!(sqrt_u128(max_root * max_root - 1), max_root - 1);
+    }
+
+    #[test]
+    fn sqrt_large_values() {
+        assert_eq!(sqrt_u128(u128::MAX), u64::MAX as u128);
+        for shift in 0..128 {
+            let power = 1u128 << shift;
+            assert_floor_sqrt(power);
+            assert_floor_sqrt(power - 1);
+            assert_floor_sqrt(power | (power >> 1));
+        }
+    }
+
+    #[test]
+    fn sqrt_matches_product_of_u64_amounts() {
+        // The initial LP supply is sqrt(a * b) for two u64 token amounts
+        let amounts = [
+            1u64,
+            2,
+            999,
+            1_000_000,
+            9_007_199_254_740_993,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for a in amounts {
+            for b in amounts {
+                assert_floor_sqrt(a as u128 * b as u128);
+            }
+        }
+        assert_eq!(
+            sqrt_u128(u64::MAX as u128 * u64::MAX as u128),
+            u64::MAX as u128
+        );
+    }
+}
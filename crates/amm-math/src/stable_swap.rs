@@ -0,0 +1,292 @@
+//! StableSwap (Curve) pricing for two pegged tokens of the same decimals
+//!
+//! A·n^n·(x + y) + D = A·D·n^n + D^(n+1) / (n^n·x·y), with n = 2 tokens. D is the
+//! invariant, equal to x + y when the pool is balanced, solved with Newton's method.
+
+use crate::error::{MathError, MathResult};
+use crate::fees::amount_after_fee;
+use crate::math::{SafeCast, SafeMath};
+use crate::u256::{CheckedU256, U256};
+use crate::{Deposit, BPS_DENOMINATOR};
+
+const N_COINS: u64 = 2;
+const MAX_ITERATIONS: usize = 255;
+
+/// Invariant D of the reserves, rounded down
+pub fn compute_d(amp: u64, reserve_a: u128, reserve_b: u128) -> MathResult<u128> {
+    if amp == 0 {
+        return Err(MathError::InvalidInput);
+    }
+    if reserve_a == 0 || reserve_b == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    let n = U256::from(N_COINS);
+    let ann = U256::from(amp).mul(n)?;
+    let (x, y) = (U256::from(reserve_a), U256::from(reserve_b));
+    let sum = x.add(y)?;
+
+    // D_{k+1} = (Ann·S + n·D_P)·D_k / ((Ann - 1)·D_k + (n + 1)·D_P), with D_P = D_k^(n+1) / (n^n·x·y)
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // One division, truncating between the factors can make the iteration cycle
+        let d_p = d.mul(d)?.mul(d)?.div(x.mul(y)?.mul(n)?.mul(n)?)?;
+        let previous = d;
+        let numerator = ann.mul(sum)?.add(d_p.mul(n)?)?.mul(d)?;
+        let denominator = ann
+            .sub(U256::one())?
+            .mul(d)?
+            .add(n.add(U256::one())?.mul(d_p)?)?;
+        d = numerator.div(denominator)?;
+
+        if d.abs_diff(previous) <= U256::one() {
+            return d.to_u128();
+        }
+    }
+
+    Err(MathError::NoConvergence)
+}
+
+/// Reserve of the other token keeping the invariant at `d` once this one is `reserve`, rounded up
+pub fn compute_y(amp: u64, reserve: u128, d: u128) -> MathResult<u128> {
+    if amp == 0 {
+        return Err(MathError::InvalidInput);
+    }
+    if reserve == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    let n = U256::from(N_COINS);
+    let ann = U256::from(amp).mul(n)?;
+    let (x, d) = (U256::from(reserve), U256::from(d));
+
+    // y^2 + (b - D)·y = c, with b = x + D / Ann and c = D^(n+1) / (n^n·x·Ann)
+    let c = d.mul(d)?.mul(d)?.div(x.mul(n)?.mul(ann)?.mul(n)?)?;
+    let b = x.add(d.div(ann)?)?;
+
+    // y_{k+1} = (y_k^2 + c) / (2·y_k + b - D)
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let previous = y;
+        let numerator = y.mul(y)?.add(c)?;
+        let denominator = y.mul(n)?.add(b)?.sub(d)?;
+        y = numerator.div(denominator)?;
+
+        if y.abs_diff(previous) <= U256::one() {
+            // The last step can land one unit under the root, round up in favor of the pool
+            return y.add(U256::one())?.to_u128();
+        }
+    }
+
+    Err(MathError::NoConvergence)
+}
+
+/// LP supply minted for the first deposit of a stable pool: D of the amounts, rounded down
+pub fn stable_initial_liquidity(amp: u64, amount_a: u64, amount_b: u64) -> MathResult<u64> {
+    compute_d(amp, amount_a as u128, amount_b as u128)?.to_u64()
+}
+
+/// Marginal price of token A in token B (Q64.64) on the stable curve, rounded down
+///
+/// -dy/dx of the invariant: (Ann·x + c)·y / ((Ann·y + c)·x), with c = D^(n+1) / (n^n·x·y)
+pub fn stable_spot_price(amp: u64, reserve_a: u64, reserve_b: u64) -> MathResult<u128> {
+    let d = compute_d(amp, reserve_a as u128, reserve_b as u128)?;
+
+    let n = U256::from(N_COINS);
+    let ann = U256::from(amp).mul(n)?;
+    let (x, y, d) = (U256::from(reserve_a), U256::from(reserve_b), U256::from(d));
+    let c = d.mul(d)?.mul(d)?.div(x.mul(y)?.mul(n)?.mul(n)?)?;
+
+    let numerator = ann.mul(x)?.add(c)?.mul(y)?;
+    let denominator = ann.mul(y)?.add(c)?.mul(x)?;
+    (numerator << 64).div(denominator)?.to_u128()
+}
+
+/// Output of an exact input swap of `amount_in` on the stable curve, after the pool's fee and rounded down
+pub fn stable_swap_exact_in(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee_bps: u16,
+) -> MathResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_in == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    // Taking the pool's fee for liquidity providers
+    let amount_in_with_fee = amount_after_fee(amount_in, fee_bps)?;
+
+    let d = compute_d(amp, reserve_in as u128, reserve_out as u128)?;
+    let new_reserve_in = (reserve_in as u128).safe_add(amount_in_with_fee as u128)?;
+    let new_reserve_out = compute_y(amp, new_reserve_in, d)?;
+
+    // One more unit stays in the pool to cover D's own rounding, a swap too small to move the
+    // curve pays out nothing
+    (reserve_out as u128)
+        .saturating_sub(new_reserve_out.safe_add(1)?)
+        .to_u64()
+}
+
+/// Input needed for an exact output swap of `amount_out` on the stable curve, fee included and rounded up
+pub fn stable_swap_exact_out(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee_bps: u16,
+) -> MathResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 || amount_out >= reserve_out {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_out == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    let d = compute_d(amp, reserve_in as u128, reserve_out as u128)?;
+    let new_reserve_out = (reserve_out as u128).safe_sub(amount_out as u128)?;
+    let new_reserve_in = compute_y(amp, new_reserve_out, d)?;
+    // One more unit in, matching the unit an exact input swap keeps to cover D's rounding
+    let amount_in_with_fee = new_reserve_in
+        .saturating_sub(reserve_in as u128)
+        .safe_add(1)?;
+
+    // Adding the pool's fee for liquidity providers on top
+    let fee_denominator = BPS_DENOMINATOR.safe_sub(fee_bps as u64)? as u128;
+    amount_in_with_fee
+        .safe_mul(BPS_DENOMINATOR as u128)?
+        .safe_div_ceil(fee_denominator)?
+        .to_u64()
+}
+
+/// Deposit of any mix of `amount_a` and `amount_b` into a stable pool, all of both is pulled
+///
+/// LP tokens follow the growth of D. The part of the deposit that isn't at the pool's ratio pays
+/// half the swap fee, like a swap into the pool would, and that fee stays with the pool.
+pub fn stable_deposit_shares(
+    amp: u64,
+    amount_a: u64,
+    amount_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    fee_bps: u16,
+) -> MathResult<Deposit> {
+    // A pool without liquidity has no share price
+    if lp_supply == 0 || reserve_a == 0 || reserve_b == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    let new_reserve_a = (reserve_a as u128).safe_add(amount_a as u128)?;
+    let new_reserve_b = (reserve_b as u128).safe_add(amount_b as u128)?;
+    let d0 = compute_d(amp, reserve_a as u128, reserve_b as u128)?;
+    let d1 = compute_d(amp, new_reserve_a, new_reserve_b)?;
+    if d1 <= d0 {
+        return Err(MathError::InsufficientAmount);
+    }
+
+    // Fee on the distance to a deposit at the pool's ratio, fee * n / (4 * (n - 1)) = fee / 2
+    let imbalance_fee = |reserve: u64, new_reserve: u128| -> MathResult<u128> {
+        let ideal_reserve = d1.safe_mul(reserve as u128)?.safe_div(d0)?;
+        ideal_reserve
+            .abs_diff(new_reserve)
+            .safe_mul(fee_bps as u128)?
+            .safe_div_ceil(2 * BPS_DENOMINATOR as u128)
+    };
+    let fee_a = imbalance_fee(reserve_a, new_reserve_a)?;
+    let fee_b = imbalance_fee(reserve_b, new_reserve_b)?;
+    let d2 = compute_d(
+        amp,
+        new_reserve_a.safe_sub(fee_a)?,
+        new_reserve_b.safe_sub(fee_b)?,
+    )?;
+    if d2 <= d0 {
+        return Err(MathError::InsufficientAmount);
+    }
+
+    // lp_minted = lp_supply * (D2 - D0) / D0, rounded down
+    let lp_minted = U256::from(lp_supply)
+        .mul(U256::from(d2.safe_sub(d0)?))?
+        .div(U256::from(d0))?;
+
+    Ok(Deposit {
+        amount_a,
+        amount_b,
+        lp_minted: lp_minted.to_u128()?.to_u64()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_exact_in;
+
+    #[test]
+    fn d_of_a_balanced_pool_is_the_sum() {
+        assert_eq!(compute_d(100, 1_000_000, 1_000_000).unwrap(), 2_000_000);
+        assert_eq!(
+            compute_d(1_000_000, u64::MAX as u128, u64::MAX as u128).unwrap(),
+            2 * u64::MAX as u128
+        );
+
+        // Imbalance lowers D below the sum, less so with a higher amplification
+        let low_amp = compute_d(1, 1_000_000, 3_000_000).unwrap();
+        let high_amp = compute_d(1_000, 1_000_000, 3_000_000).unwrap();
+        assert!(low_amp < high_amp && high_amp < 4_000_000);
+    }
+
+    #[test]
+    fn y_keeps_d() {
+        let d = compute_d(100, 5_000_000, 7_000_000).unwrap();
+        let y = compute_y(100, 5_000_000, d).unwrap();
+        assert!((7_000_000..=7_000_002).contains(&y));
+    }
+
+    #[test]
+    fn stable_swap_has_less_slippage_than_constant_product() {
+        let stable =
+            stable_swap_exact_in(100, 1_000_000_000, 1_000_000_000, 10_000_000, 1).unwrap();
+        let constant_product = swap_exact_in(1_000_000_000, 1_000_000_000, 10_000_000, 1).unwrap();
+        assert!(stable > constant_product);
+        // 1% of the pool moves the price by well under 0.01% at amp 100
+        assert!(stable > 9_998_000 && stable < 10_000_000);
+
+        let amount_in =
+            stable_swap_exact_out(100, 1_000_000_000, 1_000_000_000, stable, 1).unwrap();
+        assert!(
+            stable_swap_exact_in(100, 1_000_000_000, 1_000_000_000, amount_in, 1).unwrap()
+                >= stable
+        );
+    }
+
+    #[test]
+    fn imbalanced_deposits_pay_a_fee() {
+        let balanced =
+            stable_deposit_shares(100, 1_000, 1_000, 1_000_000, 1_000_000, 2_000_000, 30).unwrap();
+        let one_sided =
+            stable_deposit_shares(100, 2_000, 0, 1_000_000, 1_000_000, 2_000_000, 30).unwrap();
+        assert_eq!(balanced.lp_minted, 2_000);
+        assert!(one_sided.lp_minted < balanced.lp_minted);
+
+        assert_eq!(
+            stable_deposit_shares(100, 0, 0, 1_000_000, 1_000_000, 2_000_000, 30),
+            Err(MathError::InsufficientAmount)
+        );
+    }
+
+    #[test]
+    fn stable_spot_price_stays_near_the_peg() {
+        assert_eq!(
+            stable_spot_price(100, 1_000_000, 1_000_000).unwrap(),
+            1 << 64
+        );
+
+        // 3x more B than A prices A at 3 B on the constant product curve, barely above 1 B at a high amp
+        let low_amp = stable_spot_price(1, 1_000_000, 3_000_000).unwrap();
+        let high_amp = stable_spot_price(1_000, 1_000_000, 3_000_000).unwrap();
+        assert!(high_amp > 1 << 64 && high_amp < low_amp && low_amp < 3 << 64);
+    }
+}
@@ -0,0 +1,54 @@
+// 256 bit integers for the intermediate products of the curves that don't fit in a u128
+
+use crate::error::{MathError, MathResult};
+
+pub(crate) use self::inner::U256;
+
+mod inner {
+    // The lint fires inside the macro's own expansion
+    #![allow(clippy::manual_div_ceil)]
+
+    uint::construct_uint! {
+        pub(crate) struct U256(4);
+    }
+}
+
+/// Checked arithmetic on `U256` that fails with a typed `MathError` instead of panicking
+pub(crate) trait CheckedU256: Sized {
+    fn add(self, rhs: Self) -> MathResult<Self>;
+    fn sub(self, rhs: Self) -> MathResult<Self>;
+    fn mul(self, rhs: Self) -> MathResult<Self>;
+    fn div(self, rhs: Self) -> MathResult<Self>;
+    fn div_ceil(self, rhs: Self) -> MathResult<Self>;
+    fn to_u128(self) -> MathResult<u128>;
+}
+
+impl CheckedU256 for U256 {
+    fn add(self, rhs: Self) -> MathResult<Self> {
+        self.checked_add(rhs).ok_or(MathError::Overflow)
+    }
+
+    fn sub(self, rhs: Self) -> MathResult<Self> {
+        self.checked_sub(rhs).ok_or(MathError::Overflow)
+    }
+
+    fn mul(self, rhs: Self) -> MathResult<Self> {
+        self.checked_mul(rhs).ok_or(MathError::Overflow)
+    }
+
+    fn div(self, rhs: Self) -> MathResult<Self> {
+        self.checked_div(rhs).ok_or(MathError::DivisionByZero)
+    }
+
+    fn div_ceil(self, rhs: Self) -> MathResult<Self> {
+        let quotient = self.div(rhs)?;
+        match (quotient * rhs) == self {
+            true => Ok(quotient),
+            false => quotient.add(U256::one()),
+        }
+    }
+
+    fn to_u128(self) -> MathResult<u128> {
+        u128::try_from(self).map_err(|_| MathError::Overflow)
+    }
+}
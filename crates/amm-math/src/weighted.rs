@@ -0,0 +1,187 @@
+//! Weighted product (Balancer) pricing for pools holding their tokens at a fixed value split
+//!
+//! B_a^w_a · B_b^w_b = V, with the weights in basis points adding up to 10_000. Equal weights give
+//! the constant product curve. Deposits and withdrawals keep the reserve ratio, so they keep the
+//! weights and use the proportional LP share math of the constant product pools.
+
+use crate::error::{MathError, MathResult};
+use crate::fees::amount_after_fee;
+use crate::fixed_point::{
+    div_down, div_up, exp, ln, minus_max_error, mul_down, mul_up, pow_up, ONE,
+};
+use crate::math::{SafeCast, SafeMath};
+use crate::u256::{CheckedU256, U256};
+use crate::BPS_DENOMINATOR;
+
+/// Largest share of the reserves (in basis points) a single swap can add or take out, past it the
+/// power approximations lose their precision
+pub const MAX_SWAP_RATIO_BPS: u64 = 3_000;
+
+/// Output of an exact input swap of `amount_in` on the weighted curve, after the pool's fee and rounded down
+///
+/// amount_out = B_out · (1 - (B_in / (B_in + A_in))^(w_in / w_out))
+pub fn weighted_swap_exact_in(
+    reserve_in: u64,
+    weight_in_bps: u16,
+    reserve_out: u64,
+    weight_out_bps: u16,
+    amount_in: u64,
+    fee_bps: u16,
+) -> MathResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_in == 0 || weight_in_bps == 0 || weight_out_bps == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    // Taking the pool's fee for liquidity providers
+    let amount_in_with_fee = amount_after_fee(amount_in, fee_bps)? as u128;
+    if amount_in_with_fee.safe_mul(BPS_DENOMINATOR as u128)?
+        > (reserve_in as u128).safe_mul(MAX_SWAP_RATIO_BPS as u128)?
+    {
+        return Err(MathError::MaxRatioExceeded);
+    }
+
+    // A larger base and a smaller exponent both leave a larger power, so less goes out
+    let base = div_up(
+        reserve_in as u128,
+        (reserve_in as u128).safe_add(amount_in_with_fee)?,
+    )?;
+    let exponent = div_down(weight_in_bps as u128, weight_out_bps as u128)?;
+    let power = pow_up(base, exponent)?;
+
+    mul_down(reserve_out as u128, ONE.saturating_sub(power))?.to_u64()
+}
+
+/// Input needed for an exact output swap of `amount_out` on the weighted curve, fee included and rounded up
+///
+/// amount_in = B_in · ((B_out / (B_out - A_out))^(w_out / w_in) - 1)
+pub fn weighted_swap_exact_out(
+    reserve_in: u64,
+    weight_in_bps: u16,
+    reserve_out: u64,
+    weight_out_bps: u16,
+    amount_out: u64,
+    fee_bps: u16,
+) -> MathResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 || amount_out >= reserve_out {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    if amount_out == 0 || weight_in_bps == 0 || weight_out_bps == 0 {
+        return Err(MathError::InvalidInput);
+    }
+    if (amount_out as u128).safe_mul(BPS_DENOMINATOR as u128)?
+        > (reserve_out as u128).safe_mul(MAX_SWAP_RATIO_BPS as u128)?
+    {
+        return Err(MathError::MaxRatioExceeded);
+    }
+
+    // Everything rounds up, so more has to come in
+    let base = div_up(
+        reserve_out as u128,
+        (reserve_out as u128).safe_sub(amount_out as u128)?,
+    )?;
+    let exponent = div_up(weight_out_bps as u128, weight_in_bps as u128)?;
+    let power = pow_up(base, exponent)?;
+    let amount_in_with_fee = mul_up(reserve_in as u128, power.safe_sub(ONE)?)?;
+
+    // Adding the pool's fee for liquidity providers on top
+    let fee_denominator = BPS_DENOMINATOR.safe_sub(fee_bps as u64)? as u128;
+    amount_in_with_fee
+        .safe_mul(BPS_DENOMINATOR as u128)?
+        .safe_div_ceil(fee_denominator)?
+        .to_u64()
+}
+
+/// LP supply minted for the first deposit of a weighted pool: the invariant V of the amounts, rounded down
+///
+/// Equal weights make it sqrt(amount_a · amount_b), like a constant product pool
+pub fn weighted_initial_liquidity(
+    amount_a: u64,
+    weight_a_bps: u16,
+    amount_b: u64,
+    weight_b_bps: u16,
+) -> MathResult<u64> {
+    if amount_a == 0 || amount_b == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    // V = e^(w_a · ln(a) + w_b · ln(b)), a single exp keeps the intermediate values in range
+    let ln_weighted = |amount: u64, weight_bps: u16| -> MathResult<i128> {
+        let ln_amount = ln((amount as u128).safe_mul(ONE)?)?;
+        Ok(ln_amount * weight_bps as i128 / BPS_DENOMINATOR as i128)
+    };
+    let exponent = ln_weighted(amount_a, weight_a_bps)? + ln_weighted(amount_b, weight_b_bps)?;
+    let invariant = minus_max_error(exp(exponent)?)?;
+
+    invariant.safe_div(ONE)?.to_u64()
+}
+
+/// Marginal price of token A in token B (Q64.64) on the weighted curve: (B_b / w_b) / (B_a / w_a), rounded down
+pub fn weighted_spot_price(
+    reserve_a: u64,
+    weight_a_bps: u16,
+    reserve_b: u64,
+    weight_b_bps: u16,
+) -> MathResult<u128> {
+    if reserve_a == 0 || reserve_b == 0 {
+        return Err(MathError::EmptyPool);
+    }
+
+    let numerator = U256::from(reserve_b).mul(U256::from(weight_a_bps))?;
+    let denominator = U256::from(reserve_a).mul(U256::from(weight_b_bps))?;
+    (numerator << 64).div(denominator)?.to_u128()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{initial_liquidity, swap_exact_in, swap_exact_out};
+
+    #[test]
+    fn equal_weights_follow_the_constant_product() {
+        let weighted =
+            weighted_swap_exact_in(1_000_000, 5_000, 1_000_000, 5_000, 10_000, 30).unwrap();
+        let constant_product = swap_exact_in(1_000_000, 1_000_000, 10_000, 30).unwrap();
+        assert!(weighted <= constant_product && weighted + 1 >= constant_product);
+
+        let weighted =
+            weighted_swap_exact_out(1_000_000, 5_000, 1_000_000, 5_000, 10_000, 30).unwrap();
+        let constant_product = swap_exact_out(1_000_000, 1_000_000, 10_000, 30).unwrap();
+        assert!(weighted >= constant_product && weighted <= constant_product + 1);
+
+        let weighted = weighted_initial_liquidity(4_000_000, 5_000, 1_000_000, 5_000).unwrap();
+        let constant_product = initial_liquidity(4_000_000, 1_000_000).unwrap();
+        assert!(weighted <= constant_product && weighted + 1 >= constant_product);
+    }
+
+    #[test]
+    fn weights_set_the_price() {
+        assert_eq!(
+            weighted_spot_price(4_000_000, 8_000, 250_000, 2_000).unwrap(),
+            1 << 62
+        );
+
+        // 80/20 pool holding 4x more value in A: 4_000_000 A for 250_000 B prices 1 A at 0.25 B
+        let amount_out =
+            weighted_swap_exact_in(4_000_000, 8_000, 250_000, 2_000, 1_000, 0).unwrap();
+        assert_eq!(amount_out, 249);
+
+        let amount_out =
+            weighted_swap_exact_in(250_000, 2_000, 4_000_000, 8_000, 1_000, 0).unwrap();
+        assert!((3_990..4_000).contains(&amount_out));
+    }
+
+    #[test]
+    fn large_swaps_are_rejected() {
+        assert_eq!(
+            weighted_swap_exact_in(1_000_000, 5_000, 1_000_000, 5_000, 300_001, 0),
+            Err(MathError::MaxRatioExceeded)
+        );
+        assert_eq!(
+            weighted_swap_exact_out(1_000_000, 5_000, 1_000_000, 5_000, 300_001, 0),
+            Err(MathError::MaxRatioExceeded)
+        );
+    }
+}
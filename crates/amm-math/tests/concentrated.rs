@@ -0,0 +1,96 @@
+// Property tests of the concentrated liquidity math: ticks bracket their prices and swaps never pay out for free
+
+use amm_math::{
+    amounts_for_liquidity, compute_swap_step, liquidity_for_amounts, sqrt_price_at_tick,
+    tick_at_sqrt_price, MAX_SQRT_PRICE, MAX_TICK, MIN_SQRT_PRICE, MIN_TICK,
+};
+use proptest::prelude::*;
+
+const FEE_TIERS_BPS: [u16; 4] = [1, 5, 30, 100];
+
+fn fee_bps() -> impl Strategy<Value = u16> {
+    prop::sample::select(FEE_TIERS_BPS.to_vec())
+}
+
+// Prices a pair of tokens can realistically trade at, from 1e-12 to 1e12
+fn tick() -> impl Strategy<Value = i32> {
+    -276_000i32..=276_000
+}
+
+fn liquidity() -> impl Strategy<Value = u128> {
+    1_000u128..=1_000_000_000_000_000
+}
+
+proptest! {
+    #[test]
+    fn ticks_bracket_their_sqrt_price(sqrt_price in MIN_SQRT_PRICE..=MAX_SQRT_PRICE) {
+        let tick = tick_at_sqrt_price(sqrt_price).unwrap();
+        prop_assert!(sqrt_price_at_tick(tick).unwrap() <= sqrt_price);
+        if tick < MAX_TICK {
+            prop_assert!(sqrt_price_at_tick(tick + 1).unwrap() > sqrt_price);
+        }
+    }
+
+    #[test]
+    fn sqrt_price_increases_with_the_tick(tick in MIN_TICK..MAX_TICK) {
+        prop_assert!(sqrt_price_at_tick(tick).unwrap() < sqrt_price_at_tick(tick + 1).unwrap());
+    }
+
+    #[test]
+    fn round_trips_never_profit(
+        tick in tick(),
+        liquidity in liquidity(),
+        amount_in in 1u64..=1_000_000_000_000,
+        a_to_b: bool,
+        fee_bps in fee_bps(),
+    ) {
+        let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+        let (there, back) = match a_to_b {
+            true => (MIN_SQRT_PRICE, MAX_SQRT_PRICE),
+            false => (MAX_SQRT_PRICE, MIN_SQRT_PRICE),
+        };
+
+        let Ok(first) = compute_swap_step(sqrt_price, there, liquidity, amount_in, fee_bps) else {
+            return Ok(());
+        };
+        prop_assert!(first.amount_in + first.fee_amount <= amount_in);
+        if first.sqrt_price_next != there {
+            prop_assert_eq!(first.amount_in + first.fee_amount, amount_in);
+        }
+        if first.amount_out == 0 {
+            return Ok(());
+        }
+
+        // Swapping the output back can't give more than what went in, even without a fee
+        let second = compute_swap_step(first.sqrt_price_next, back, liquidity, first.amount_out, 0).unwrap();
+        prop_assert!(second.amount_out <= first.amount_in);
+    }
+
+    #[test]
+    fn deposits_never_take_more_than_the_amounts(
+        tick in tick(),
+        lower_offset in 1i32..=20_000,
+        upper_offset in 1i32..=20_000,
+        shift in -30_000i32..=30_000,
+        amount_a in 0u64..=1_000_000_000_000,
+        amount_b in 0u64..=1_000_000_000_000,
+    ) {
+        let sqrt_price = sqrt_price_at_tick(tick + shift).unwrap();
+        let sqrt_price_lower = sqrt_price_at_tick(tick - lower_offset).unwrap();
+        let sqrt_price_upper = sqrt_price_at_tick(tick + upper_offset).unwrap();
+
+        let liquidity =
+            liquidity_for_amounts(sqrt_price, sqrt_price_lower, sqrt_price_upper, amount_a, amount_b)
+                .unwrap();
+        let (deposit_a, deposit_b) =
+            amounts_for_liquidity(sqrt_price, sqrt_price_lower, sqrt_price_upper, liquidity, true)
+                .unwrap();
+        prop_assert!(deposit_a <= amount_a && deposit_b <= amount_b);
+
+        // Withdrawing rounds down, never more than what was deposited
+        let (withdraw_a, withdraw_b) =
+            amounts_for_liquidity(sqrt_price, sqrt_price_lower, sqrt_price_upper, liquidity, false)
+                .unwrap();
+        prop_assert!(withdraw_a <= deposit_a && withdraw_b <= deposit_b);
+    }
+}
@@ -0,0 +1,333 @@
+// Property tests of the constant product math, on single operations and on random sequences of them
+// applied to a model of the pool with the same bookkeeping as the program
+
+use amm_math::{
+    deposit_shares, initial_liquidity, protocol_fee, swap_exact_in, swap_exact_out, swap_fee,
+    withdrawal_amounts,
+};
+use proptest::prelude::*;
+
+const MINIMUM_LIQUIDITY: u64 = 1_000;
+const FEE_TIERS_BPS: [u16; 4] = [1, 5, 30, 100];
+
+fn reserve() -> impl Strategy<Value = u64> {
+    1u64..=1_000_000_000_000
+}
+
+fn fee_bps() -> impl Strategy<Value = u16> {
+    prop::sample::select(FEE_TIERS_BPS.to_vec())
+}
+
+/// Full 256-bit product of two u128, as (high, low)
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let mid_1 = a_hi * b_lo;
+    let mid_2 = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (mid_1 & MASK) + (mid_2 & MASK);
+    let low = (lo_lo & MASK) | (mid << 64);
+    let high = hi_hi + (mid_1 >> 64) + (mid_2 >> 64) + (mid >> 64);
+    (high, low)
+}
+
+fn k(reserve_a: u64, reserve_b: u64) -> u128 {
+    reserve_a as u128 * reserve_b as u128
+}
+
+proptest! {
+    #[test]
+    fn swap_exact_in_never_decreases_k(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=1_000_000_000_000,
+        fee_bps in fee_bps(),
+    ) {
+        let Ok(amount_out) = swap_exact_in(reserve_in, reserve_out, amount_in, fee_bps) else {
+            return Ok(());
+        };
+        prop_assert!(amount_out < reserve_out);
+
+        // Even when only the amount after fee is added, the product can't go down
+        let amount_in_with_fee = amount_in - swap_fee(amount_in, fee_bps).unwrap();
+        prop_assert!(
+            k(reserve_in + amount_in_with_fee, reserve_out - amount_out) >= k(reserve_in, reserve_out)
+        );
+
+        // Rounded down but not more than needed: one more unit out would decrease k
+        if amount_out + 1 < reserve_out {
+            prop_assert!(
+                k(reserve_in + amount_in_with_fee, reserve_out - amount_out - 1)
+                    < k(reserve_in, reserve_out)
+            );
+        }
+    }
+
+    #[test]
+    fn swap_exact_out_pays_enough(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_out in 1u64..=1_000_000_000_000,
+        fee_bps in fee_bps(),
+    ) {
+        let Ok(amount_in) = swap_exact_out(reserve_in, reserve_out, amount_out, fee_bps) else {
+            return Ok(());
+        };
+
+        // Selling what was quoted gets at least the requested amount out
+        let received = swap_exact_in(reserve_in, reserve_out, amount_in, fee_bps).unwrap();
+        prop_assert!(received >= amount_out);
+
+        let amount_in_with_fee = amount_in - swap_fee(amount_in, fee_bps).unwrap();
+        prop_assert!(
+            k(reserve_in + amount_in_with_fee, reserve_out - amount_out) >= k(reserve_in, reserve_out)
+        );
+    }
+
+    #[test]
+    fn deposit_rounds_in_favor_of_the_pool(
+        reserve_a in reserve(),
+        reserve_b in reserve(),
+        lp_supply in 1u64..=1_000_000_000_000,
+        amount_a in 0u64..=1_000_000_000_000,
+        amount_b in 0u64..=1_000_000_000_000,
+    ) {
+        // Overflows when the LP tokens minted don't fit a u64, the deposit is rejected
+        let Ok(deposit) = deposit_shares(amount_a, amount_b, reserve_a, reserve_b, lp_supply) else {
+            return Ok(());
+        };
+
+        prop_assert!(deposit.amount_a <= amount_a && deposit.amount_b <= amount_b);
+        // lp_minted / lp_supply <= deposit / reserve, on both sides
+        prop_assert!(
+            deposit.lp_minted as u128 * reserve_a as u128 <= deposit.amount_a as u128 * lp_supply as u128
+        );
+        prop_assert!(
+            deposit.lp_minted as u128 * reserve_b as u128 <= deposit.amount_b as u128 * lp_supply as u128
+        );
+    }
+
+    #[test]
+    fn withdrawal_rounds_in_favor_of_the_pool(
+        reserve_a in reserve(),
+        reserve_b in reserve(),
+        lp_supply in 1u64..=1_000_000_000_000,
+        share in 0u64..=1_000_000,
+    ) {
+        let lp_amount = (lp_supply as u128 * share as u128 / 1_000_000) as u64;
+        let withdrawal = withdrawal_amounts(lp_amount, reserve_a, reserve_b, lp_supply).unwrap();
+
+        // withdrawal / reserve <= lp_amount / lp_supply, on both sides
+        prop_assert!(
+            withdrawal.amount_a as u128 * lp_supply as u128 <= lp_amount as u128 * reserve_a as u128
+        );
+        prop_assert!(
+            withdrawal.amount_b as u128 * lp_supply as u128 <= lp_amount as u128 * reserve_b as u128
+        );
+    }
+
+    #[test]
+    fn add_then_remove_never_profits(
+        reserve_a in reserve(),
+        reserve_b in reserve(),
+        lp_supply in 1u64..=1_000_000_000_000,
+        amount_a in 0u64..=1_000_000_000_000,
+        amount_b in 0u64..=1_000_000_000_000,
+    ) {
+        // Overflows when the LP tokens minted don't fit a u64, the deposit is rejected
+        let Ok(deposit) = deposit_shares(amount_a, amount_b, reserve_a, reserve_b, lp_supply) else {
+            return Ok(());
+        };
+        let withdrawal = withdrawal_amounts(
+            deposit.lp_minted,
+            reserve_a + deposit.amount_a,
+            reserve_b + deposit.amount_b,
+            lp_supply + deposit.lp_minted,
+        )
+        .unwrap();
+
+        prop_assert!(withdrawal.amount_a <= deposit.amount_a);
+        prop_assert!(withdrawal.amount_b <= deposit.amount_b);
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    SwapExactIn { a_to_b: bool, amount: u64 },
+    SwapExactOut { a_to_b: bool, amount: u64 },
+    Deposit { amount_a: u64, amount_b: u64 },
+    // Share of the withdrawable LP supply, in millionths
+    Withdraw { share: u64 },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (any::<bool>(), 1u64..=1_000_000_000)
+            .prop_map(|(a_to_b, amount)| Op::SwapExactIn { a_to_b, amount }),
+        (any::<bool>(), 1u64..=1_000_000_000)
+            .prop_map(|(a_to_b, amount)| Op::SwapExactOut { a_to_b, amount }),
+        (0u64..=1_000_000_000, 0u64..=1_000_000_000)
+            .prop_map(|(amount_a, amount_b)| Op::Deposit { amount_a, amount_b }),
+        (0u64..=1_000_000).prop_map(|share| Op::Withdraw { share }),
+    ]
+}
+
+/// Reserves, LP supply and protocol fees tracked the way the program tracks them
+#[derive(Clone, Debug)]
+struct PoolModel {
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    protocol_fees_a: u64,
+    protocol_fees_b: u64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+}
+
+impl PoolModel {
+    // A failing operation is a rejected transaction, the state stays as it was
+    fn apply(&mut self, op: &Op) {
+        let _ = match *op {
+            Op::SwapExactIn { a_to_b, amount } => self.swap(a_to_b, Some(amount), None),
+            Op::SwapExactOut { a_to_b, amount } => self.swap(a_to_b, None, Some(amount)),
+            Op::Deposit { amount_a, amount_b } => self.deposit(amount_a, amount_b),
+            Op::Withdraw { share } => self.withdraw(share),
+        };
+    }
+
+    fn swap(
+        &mut self,
+        a_to_b: bool,
+        amount_in: Option<u64>,
+        amount_out: Option<u64>,
+    ) -> Option<()> {
+        let (reserve_in, reserve_out) = match a_to_b {
+            true => (self.reserve_a, self.reserve_b),
+            false => (self.reserve_b, self.reserve_a),
+        };
+        let (amount_in, amount_out) = match (amount_in, amount_out) {
+            (Some(amount_in), _) => (
+                amount_in,
+                swap_exact_in(reserve_in, reserve_out, amount_in, self.fee_bps).ok()?,
+            ),
+            (_, Some(amount_out)) => (
+                swap_exact_out(reserve_in, reserve_out, amount_out, self.fee_bps).ok()?,
+                amount_out,
+            ),
+            _ => unreachable!(),
+        };
+        let protocol_fee = protocol_fee(
+            swap_fee(amount_in, self.fee_bps).ok()?,
+            self.protocol_fee_bps,
+        )
+        .ok()?;
+
+        let reserve_in = reserve_in.checked_add(amount_in - protocol_fee)?;
+        let reserve_out = reserve_out - amount_out;
+        match a_to_b {
+            true => {
+                (self.reserve_a, self.reserve_b) = (reserve_in, reserve_out);
+                self.protocol_fees_a += protocol_fee;
+            }
+            false => {
+                (self.reserve_b, self.reserve_a) = (reserve_in, reserve_out);
+                self.protocol_fees_b += protocol_fee;
+            }
+        }
+        Some(())
+    }
+
+    fn deposit(&mut self, amount_a: u64, amount_b: u64) -> Option<()> {
+        let deposit = deposit_shares(
+            amount_a,
+            amount_b,
+            self.reserve_a,
+            self.reserve_b,
+            self.lp_supply,
+        )
+        .ok()?;
+        self.reserve_a = self.reserve_a.checked_add(deposit.amount_a)?;
+        self.reserve_b = self.reserve_b.checked_add(deposit.amount_b)?;
+        self.lp_supply = self.lp_supply.checked_add(deposit.lp_minted)?;
+        Some(())
+    }
+
+    fn withdraw(&mut self, share: u64) -> Option<()> {
+        // The MINIMUM_LIQUIDITY locked on creation can never be burned
+        let withdrawable = self.lp_supply - MINIMUM_LIQUIDITY;
+        let lp_amount = (withdrawable as u128 * share as u128 / 1_000_000) as u64;
+        let withdrawal =
+            withdrawal_amounts(lp_amount, self.reserve_a, self.reserve_b, self.lp_supply).ok()?;
+        self.reserve_a -= withdrawal.amount_a;
+        self.reserve_b -= withdrawal.amount_b;
+        self.lp_supply -= lp_amount;
+        Some(())
+    }
+
+    /// reserve_a * reserve_b / lp_supply^2, compared as 256-bit cross products
+    fn value_per_share_at_least(&self, before: &PoolModel) -> bool {
+        let after = mul_wide(
+            k(self.reserve_a, self.reserve_b),
+            before.lp_supply as u128 * before.lp_supply as u128,
+        );
+        let before = mul_wide(
+            k(before.reserve_a, before.reserve_b),
+            self.lp_supply as u128 * self.lp_supply as u128,
+        );
+        after >= before
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn random_sequences_keep_the_pool_solvent(
+        amount_a in 1_000_000u64..=1_000_000_000_000,
+        amount_b in 1_000_000u64..=1_000_000_000_000,
+        fee_bps in fee_bps(),
+        protocol_fee_bps in 0u16..=10_000,
+        ops in prop::collection::vec(op(), 1..64),
+    ) {
+        let mut pool = PoolModel {
+            reserve_a: amount_a,
+            reserve_b: amount_b,
+            lp_supply: initial_liquidity(amount_a, amount_b).unwrap(),
+            protocol_fees_a: 0,
+            protocol_fees_b: 0,
+            fee_bps,
+            protocol_fee_bps,
+        };
+
+        for op in &ops {
+            let before = pool.clone();
+            pool.apply(op);
+
+            // Nobody can drain a side of the pool or burn the locked liquidity
+            prop_assert!(pool.reserve_a > 0 && pool.reserve_b > 0, "{op:?} emptied {pool:?}");
+            prop_assert!(pool.lp_supply >= MINIMUM_LIQUIDITY);
+            // Protocol fees only ever accrue
+            prop_assert!(pool.protocol_fees_a >= before.protocol_fees_a);
+            prop_assert!(pool.protocol_fees_b >= before.protocol_fees_b);
+            // Swaps never decrease k and no operation dilutes the existing LP tokens
+            if matches!(op, Op::SwapExactIn { .. } | Op::SwapExactOut { .. }) {
+                prop_assert!(k(pool.reserve_a, pool.reserve_b) >= k(before.reserve_a, before.reserve_b));
+            }
+            prop_assert!(pool.value_per_share_at_least(&before), "{op:?} diluted {before:?} into {pool:?}");
+        }
+    }
+}
+
+#[test]
+fn mul_wide_matches_u128_when_it_fits() {
+    assert_eq!(
+        mul_wide(u64::MAX as u128, u64::MAX as u128),
+        (0, u64::MAX as u128 * u64::MAX as u128)
+    );
+    assert_eq!(mul_wide(u128::MAX, 2), (1, u128::MAX - 1));
+    assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+}
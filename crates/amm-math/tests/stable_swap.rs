@@ -0,0 +1,112 @@
+// Property tests of the StableSwap math: swaps never lower D and LP tokens never lose D per share
+
+use amm_math::{
+    compute_d, stable_deposit_shares, stable_initial_liquidity, stable_swap_exact_in,
+    stable_swap_exact_out, swap_fee, withdrawal_amounts,
+};
+use proptest::prelude::*;
+
+const FEE_TIERS_BPS: [u16; 4] = [1, 5, 30, 100];
+
+fn reserve() -> impl Strategy<Value = u64> {
+    1_000u64..=1_000_000_000_000
+}
+
+fn amp() -> impl Strategy<Value = u64> {
+    prop_oneof![1u64..=10, 10u64..=10_000, 10_000u64..=1_000_000]
+}
+
+fn fee_bps() -> impl Strategy<Value = u16> {
+    prop::sample::select(FEE_TIERS_BPS.to_vec())
+}
+
+fn d(amp: u64, reserve_a: u64, reserve_b: u64) -> u128 {
+    compute_d(amp, reserve_a as u128, reserve_b as u128).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn swap_exact_in_never_decreases_d(
+        amp in amp(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=1_000_000_000_000,
+        fee_bps in fee_bps(),
+    ) {
+        let Ok(amount_out) = stable_swap_exact_in(amp, reserve_in, reserve_out, amount_in, fee_bps)
+        else {
+            return Ok(());
+        };
+        prop_assert!(amount_out < reserve_out);
+
+        // Even when only the amount after fee is added, the invariant can't go down
+        let amount_in_with_fee = amount_in - swap_fee(amount_in, fee_bps).unwrap();
+        prop_assert!(
+            d(amp, reserve_in + amount_in_with_fee, reserve_out - amount_out)
+                >= d(amp, reserve_in, reserve_out)
+        );
+    }
+
+    #[test]
+    fn swap_exact_out_pays_enough(
+        amp in amp(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_out in 1u64..=1_000_000_000_000,
+        fee_bps in fee_bps(),
+    ) {
+        let Ok(amount_in) = stable_swap_exact_out(amp, reserve_in, reserve_out, amount_out, fee_bps)
+        else {
+            return Ok(());
+        };
+
+        let amount_in_with_fee = amount_in - swap_fee(amount_in, fee_bps).unwrap();
+        let Some(new_reserve_in) = reserve_in.checked_add(amount_in_with_fee) else {
+            return Ok(());
+        };
+        prop_assert!(
+            d(amp, new_reserve_in, reserve_out - amount_out) >= d(amp, reserve_in, reserve_out)
+        );
+    }
+
+    #[test]
+    fn deposits_and_withdrawals_never_dilute_lps(
+        amp in amp(),
+        reserve_a in reserve(),
+        reserve_b in reserve(),
+        amount_a in 0u64..=1_000_000_000_000,
+        amount_b in 0u64..=1_000_000_000_000,
+        fee_bps in fee_bps(),
+    ) {
+        let Ok(lp_supply) = stable_initial_liquidity(amp, reserve_a, reserve_b) else {
+            return Ok(());
+        };
+        let Ok(deposit) = stable_deposit_shares(
+            amp, amount_a, amount_b, reserve_a, reserve_b, lp_supply, fee_bps,
+        ) else {
+            return Ok(());
+        };
+        prop_assert_eq!((deposit.amount_a, deposit.amount_b), (amount_a, amount_b));
+
+        // D per LP token never goes down, whatever the mix deposited
+        let (deposited_a, deposited_b) = (reserve_a + amount_a, reserve_b + amount_b);
+        let deposited_supply = lp_supply + deposit.lp_minted;
+        prop_assert!(
+            d(amp, deposited_a, deposited_b) * lp_supply as u128
+                >= d(amp, reserve_a, reserve_b) * deposited_supply as u128
+        );
+
+        // Nor does it once the new LP tokens are burned for their proportional share
+        let withdrawal = withdrawal_amounts(
+            deposit.lp_minted,
+            deposited_a,
+            deposited_b,
+            deposited_supply,
+        )
+        .unwrap();
+        prop_assert!(
+            d(amp, deposited_a - withdrawal.amount_a, deposited_b - withdrawal.amount_b)
+                >= d(amp, reserve_a, reserve_b)
+        );
+    }
+}
@@ -0,0 +1,85 @@
+// Property tests of the weighted math: equal weights never beat the exact constant product math and
+// other weights stay within the error bound of the float formulas
+
+use amm_math::{
+    initial_liquidity, swap_exact_in, swap_exact_out, weighted_initial_liquidity,
+    weighted_swap_exact_in, weighted_swap_exact_out, MathError,
+};
+use proptest::prelude::*;
+
+const FEE_TIERS_BPS: [u16; 4] = [1, 5, 30, 100];
+
+fn reserve() -> impl Strategy<Value = u64> {
+    1_000u64..=1_000_000_000_000
+}
+
+fn weight_bps() -> impl Strategy<Value = u16> {
+    100u16..=9_900
+}
+
+// Share of a reserve in parts per ten million, up to the largest swap a weighted pool takes
+fn share() -> impl Strategy<Value = u64> {
+    1u64..=3_000_000
+}
+
+fn share_of(reserve: u64, share: u64) -> u64 {
+    (reserve as u128 * share as u128 / 10_000_000).max(1) as u64
+}
+
+fn fee_bps() -> impl Strategy<Value = u16> {
+    prop::sample::select(FEE_TIERS_BPS.to_vec())
+}
+
+proptest! {
+    #[test]
+    fn equal_weights_round_like_the_constant_product(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        share in share(),
+        fee_bps in fee_bps(),
+    ) {
+        let amount = share_of(reserve_in.min(reserve_out), share);
+
+        // The constant product math is exact, the weighted one can only round further for the pool
+        if let Ok(amount_out) = weighted_swap_exact_in(reserve_in, 5_000, reserve_out, 5_000, amount, fee_bps) {
+            let exact = swap_exact_in(reserve_in, reserve_out, amount, fee_bps).unwrap();
+            prop_assert!(amount_out <= exact);
+            prop_assert!(amount_out as f64 >= exact as f64 - reserve_out as f64 * 1e-13 - 2.0);
+        }
+
+        match weighted_swap_exact_out(reserve_in, 5_000, reserve_out, 5_000, amount, fee_bps) {
+            Ok(amount_in) => {
+                let exact = swap_exact_out(reserve_in, reserve_out, amount, fee_bps).unwrap();
+                prop_assert!(amount_in >= exact);
+                prop_assert!(amount_in as f64 <= exact as f64 * (1.0 + 1e-12) + 2.0);
+            }
+            Err(err) => prop_assert!(matches!(err, MathError::MaxRatioExceeded | MathError::Overflow | MathError::InsufficientLiquidity)),
+        }
+
+        let weighted = weighted_initial_liquidity(reserve_in, 5_000, reserve_out, 5_000).unwrap();
+        let exact = initial_liquidity(reserve_in, reserve_out).unwrap();
+        prop_assert!(weighted <= exact && weighted as f64 >= exact as f64 * (1.0 - 1e-13) - 1.0);
+    }
+
+    #[test]
+    fn swaps_match_the_float_formulas(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        weight_in_bps in weight_bps(),
+        share in share(),
+    ) {
+        let amount_in = share_of(reserve_in, share);
+        let weight_out_bps = 10_000 - weight_in_bps;
+        let Ok(amount_out) =
+            weighted_swap_exact_in(reserve_in, weight_in_bps, reserve_out, weight_out_bps, amount_in, 0)
+        else {
+            return Ok(());
+        };
+
+        let (b_in, b_out, a_in) = (reserve_in as f64, reserve_out as f64, amount_in as f64);
+        let exponent = weight_in_bps as f64 / weight_out_bps as f64;
+        let expected = b_out * (1.0 - (b_in / (b_in + a_in)).powf(exponent));
+        let tolerance = b_out * 1e-12 + 2.0;
+        prop_assert!((amount_out as f64 - expected).abs() <= tolerance, "{amount_out} vs {expected}");
+    }
+}
@@ -0,0 +1,8 @@
+//! Anchor CPIs through `solana_invoke`, whose off-chain syscall stub is `unimplemented!`. The wrappers in
+//! `solana_program::program` have the same signatures and forward to the syscall stubs solana-program-test
+//! installs, so the program can run as a native builtin in the integration tests. On the SBF target both
+//! end up in the same syscall.
+
+pub use solana_program::program::{
+    invoke, invoke_signed, invoke_signed_unchecked, invoke_unchecked,
+};
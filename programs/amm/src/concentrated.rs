@@ -0,0 +1,164 @@
+use amm_math::{MAX_TICK, MIN_TICK};
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+use crate::math::SafeMath;
+use crate::state::{Pool, Position, Tick, TickArray};
+
+// Tick arrays of a concentrated liquidity pool loaded from the instruction's accounts. They are deserialized
+// by hand so the same array can be passed more than once, e.g. as both bounds of a narrow position
+pub struct TickArrays<'a, 'info> {
+    tick_spacing: u16,
+    arrays: Vec<(&'a AccountInfo<'info>, TickArray)>,
+}
+
+impl<'a, 'info> TickArrays<'a, 'info> {
+    pub fn load(pool: &Account<Pool>, accounts: &'a [AccountInfo<'info>]) -> Result<Self> {
+        let mut arrays: Vec<(&'a AccountInfo<'info>, TickArray)> =
+            Vec::with_capacity(accounts.len());
+        for account in accounts {
+            if arrays.iter().any(|(loaded, _)| loaded.key == account.key) {
+                continue;
+            }
+
+            // Security check for the arrays being program accounts of this pool
+            require_keys_eq!(*account.owner, crate::ID, AMMError::InvalidTickArray);
+            require!(account.is_writable, AMMError::InvalidTickArray);
+            let array = TickArray::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+            require_keys_eq!(array.pool, pool.key(), AMMError::InvalidTickArray);
+
+            arrays.push((account, array));
+        }
+
+        Ok(Self {
+            tick_spacing: pool.tick_spacing,
+            arrays,
+        })
+    }
+
+    fn array(&self, start_tick_index: i32) -> Result<&TickArray> {
+        self.arrays
+            .iter()
+            .map(|(_, array)| array)
+            .find(|array| array.start_tick_index == start_tick_index)
+            .ok_or_else(|| error!(AMMError::TickArrayMissing))
+    }
+
+    pub fn tick_mut(&mut self, tick_index: i32) -> Result<&mut Tick> {
+        let start = TickArray::start_tick_index(tick_index, self.tick_spacing);
+        let array = self
+            .arrays
+            .iter_mut()
+            .map(|(_, array)| array)
+            .find(|array| array.start_tick_index == start)
+            .ok_or(AMMError::TickArrayMissing)?;
+        let offset = array
+            .offset(tick_index, self.tick_spacing)
+            .ok_or(AMMError::InvalidTick)?;
+        Ok(&mut array.ticks[offset])
+    }
+
+    // Next tick a swap reaches, the next initialized one or the end of the array the search starts in
+    pub fn next_initialized_tick(&self, tick_current: i32, a_to_b: bool) -> Result<(i32, bool)> {
+        let search_start = match a_to_b {
+            true => tick_current,
+            false => tick_current + self.tick_spacing as i32,
+        };
+        let array = self.array(TickArray::start_tick_index(search_start, self.tick_spacing))?;
+        let (tick_index, initialized) = array
+            .next_initialized_tick(tick_current, self.tick_spacing, a_to_b)
+            .ok_or(AMMError::TickArrayMissing)?;
+        Ok((tick_index.clamp(MIN_TICK, MAX_TICK), initialized))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        for (account, array) in &self.arrays {
+            array.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        }
+        Ok(())
+    }
+}
+
+// Ticks bounding a position have to be usable by the pool and ordered
+pub fn check_ticks(tick_lower: i32, tick_upper: i32, tick_spacing: u16) -> Result<()> {
+    let spacing = tick_spacing as i32;
+    require!(
+        tick_lower < tick_upper
+            && tick_lower >= MIN_TICK
+            && tick_upper <= MAX_TICK
+            && tick_lower % spacing == 0
+            && tick_upper % spacing == 0,
+        AMMError::InvalidTick
+    );
+    Ok(())
+}
+
+// Adds a signed liquidity delta to a position, its ticks and the pool, accruing the fees it earned so far
+pub fn update_position(
+    pool: &mut Pool,
+    position: &mut Position,
+    tick_arrays: &mut TickArrays,
+    liquidity_delta: i128,
+) -> Result<()> {
+    let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+    tick_arrays
+        .tick_mut(tick_lower)?
+        .update(tick_lower, pool, liquidity_delta, false)?;
+    tick_arrays
+        .tick_mut(tick_upper)?
+        .update(tick_upper, pool, liquidity_delta, true)?;
+
+    // Fees earned by the position's liquidity before the change
+    let lower = *tick_arrays.tick_mut(tick_lower)?;
+    let upper = *tick_arrays.tick_mut(tick_upper)?;
+    let fee_growth_inside_a = amm_math::fee_growth_inside(
+        pool.tick_current,
+        tick_lower,
+        lower.fee_growth_outside_a,
+        tick_upper,
+        upper.fee_growth_outside_a,
+        pool.fee_growth_global_a,
+    );
+    let fee_growth_inside_b = amm_math::fee_growth_inside(
+        pool.tick_current,
+        tick_lower,
+        lower.fee_growth_outside_b,
+        tick_upper,
+        upper.fee_growth_outside_b,
+        pool.fee_growth_global_b,
+    );
+    let owed_a = amm_math::fees_owed(
+        fee_growth_inside_a,
+        position.fee_growth_inside_last_a,
+        position.liquidity,
+    )
+    .map_err(AMMError::from)?;
+    let owed_b = amm_math::fees_owed(
+        fee_growth_inside_b,
+        position.fee_growth_inside_last_b,
+        position.liquidity,
+    )
+    .map_err(AMMError::from)?;
+    position.tokens_owed_a = position.tokens_owed_a.safe_add(owed_a)?;
+    position.tokens_owed_b = position.tokens_owed_b.safe_add(owed_b)?;
+    position.fee_growth_inside_last_a = fee_growth_inside_a;
+    position.fee_growth_inside_last_b = fee_growth_inside_b;
+
+    // Ticks no position uses anymore are cleared for the next one
+    for tick_index in [tick_lower, tick_upper] {
+        let tick = tick_arrays.tick_mut(tick_index)?;
+        if tick.liquidity_gross == 0 {
+            *tick = Tick::default();
+        }
+    }
+
+    // The pool's liquidity only counts the positions in range
+    position.liquidity = amm_math::add_liquidity_delta(position.liquidity, liquidity_delta)
+        .map_err(AMMError::from)?;
+    if (tick_lower..tick_upper).contains(&pool.tick_current) {
+        pool.liquidity = amm_math::add_liquidity_delta(pool.liquidity, liquidity_delta)
+            .map_err(AMMError::from)?;
+    }
+
+    Ok(())
+}
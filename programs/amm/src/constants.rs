@@ -0,0 +1,39 @@
+/// Denominator for every basis point value used by the program
+pub const BPS_DENOMINATOR: u64 = amm_math::BPS_DENOMINATOR;
+
+/// LP tokens locked forever on pool creation so the LP supply can never go back to zero
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+/// Bounds of the amplification coefficient of a StableSwap pool, 0 creates a constant product pool instead
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Bounds on a ramp of the amplification coefficient, a sudden change of the curve can be arbitraged against
+/// the liquidity providers. A ramp lasts at least a day, can't start within a day of the previous one and at
+/// most multiplies or divides the current amp by 10
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Smallest weight (in basis points) of either token of a weighted pool, 1% to 99% at the extremes
+pub const MIN_WEIGHT_BPS: u16 = 100;
+
+/// Largest spacing between the usable ticks of a concentrated liquidity pool, 1_000 ticks is a 10% price move
+pub const MAX_TICK_SPACING: u16 = 1_000;
+
+/// Ticks held by a tick array of a concentrated liquidity pool
+pub const TICK_ARRAY_SIZE: usize = 32;
+
+/// Swap fee tiers (in basis points) the config starts with, pools can only be created with one of these
+/// 1 & 5 bps for stable pairs, 30 bps as the standard tier and 100 bps for long-tail pairs
+pub const DEFAULT_FEE_TIERS_BPS: [u16; 4] = [1, 5, 30, 100];
+
+/// Share of every swap fee (in basis points of the fee) the config starts with, accrued for the protocol treasury
+/// 1_667 bps is roughly 1/6th of the fee, the rest stays with the liquidity providers
+pub const DEFAULT_PROTOCOL_FEE_BPS: u16 = 1_667;
+
+/// Status bits on the pool and the config, a set bit halts the matching instruction
+pub const STATUS_SWAP_DISABLED: u8 = 1 << 0;
+pub const STATUS_DEPOSIT_DISABLED: u8 = 1 << 1;
+pub const STATUS_WITHDRAW_DISABLED: u8 = 1 << 2;
+pub const STATUS_ALL_DISABLED: u8 =
+    STATUS_SWAP_DISABLED | STATUS_DEPOSIT_DISABLED | STATUS_WITHDRAW_DISABLED;
@@ -0,0 +1,524 @@
+use amm_math::{Deposit, Withdrawal, MAX_SQRT_PRICE, MIN_SQRT_PRICE};
+use anchor_lang::prelude::*;
+
+use crate::constants::{BPS_DENOMINATOR, MAX_AMP, MAX_TICK_SPACING, MIN_AMP, MIN_WEIGHT_BPS};
+use crate::errors::AMMError;
+use crate::state::{CurveType, Pool};
+
+// Pricing model of a pool. The swap and liquidity instructions only go through this trait, so a new curve is
+// a new CurveType variant and an implementation of it. The curve parameters are stored on the pool
+pub trait SwapCurve {
+    // Checks the curve parameters of a new pool
+    fn validate(&self, pool: &Pool) -> Result<()>;
+
+    // Output of an exact input swap, after the pool's fee and rounded down
+    fn swap_exact_in(&self, pool: &Pool, amount_in: u64, a_to_b: bool) -> Result<u64>;
+
+    // Input needed for an exact output swap, fee included and rounded up
+    fn swap_exact_out(&self, pool: &Pool, amount_out: u64, a_to_b: bool) -> Result<u64>;
+
+    // Amounts pulled and LP tokens minted for a deposit, the initial liquidity while the LP supply is 0
+    fn deposit_shares(
+        &self,
+        pool: &Pool,
+        amount_a: u64,
+        amount_b: u64,
+        lp_supply: u64,
+    ) -> Result<Deposit>;
+
+    // Amounts paid out for burning `lp_amount` LP tokens, rounded down
+    fn withdraw_amounts(&self, pool: &Pool, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal>;
+
+    // Marginal price of token A in token B (Q64.64) at unix timestamp `now`, which a ramping amp depends on
+    fn spot_price(&self, pool: &Pool, now: i64) -> Result<u128>;
+}
+
+pub struct ConstantProductCurve;
+pub struct StableSwapCurve;
+pub struct WeightedCurve;
+pub struct ConcentratedCurve;
+
+// Reserves and weights of a swap's input and output side
+fn sides(pool: &Pool, a_to_b: bool) -> ((u64, u16), (u64, u16)) {
+    let side_a = (pool.reserve_a, pool.weight_a_bps);
+    let side_b = (pool.reserve_b, pool.weight_b_bps);
+    match a_to_b {
+        true => (side_a, side_b),
+        false => (side_b, side_a),
+    }
+}
+
+fn amp(pool: &Pool) -> Result<u64> {
+    Ok(pool.current_amp(Clock::get()?.unix_timestamp))
+}
+
+// Withdrawals are proportional on every curve with LP tokens, which keeps the weights of a weighted pool
+fn proportional_withdrawal(pool: &Pool, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal> {
+    let withdrawal =
+        amm_math::withdrawal_amounts(lp_amount, pool.reserve_a, pool.reserve_b, lp_supply);
+    Ok(withdrawal.map_err(AMMError::from)?)
+}
+
+impl SwapCurve for ConstantProductCurve {
+    fn validate(&self, pool: &Pool) -> Result<()> {
+        require!(
+            pool.target_amp == 0 && pool.weight_a_bps == 0 && pool.tick_spacing == 0,
+            AMMError::InvalidInput
+        );
+        Ok(())
+    }
+
+    fn swap_exact_in(&self, pool: &Pool, amount_in: u64, a_to_b: bool) -> Result<u64> {
+        let ((reserve_in, _), (reserve_out, _)) = sides(pool, a_to_b);
+        let amount_out = amm_math::swap_exact_in(reserve_in, reserve_out, amount_in, pool.fee_bps);
+        Ok(amount_out.map_err(AMMError::from)?)
+    }
+
+    fn swap_exact_out(&self, pool: &Pool, amount_out: u64, a_to_b: bool) -> Result<u64> {
+        let ((reserve_in, _), (reserve_out, _)) = sides(pool, a_to_b);
+        let amount_in = amm_math::swap_exact_out(reserve_in, reserve_out, amount_out, pool.fee_bps);
+        Ok(amount_in.map_err(AMMError::from)?)
+    }
+
+    fn deposit_shares(
+        &self,
+        pool: &Pool,
+        amount_a: u64,
+        amount_b: u64,
+        lp_supply: u64,
+    ) -> Result<Deposit> {
+        // sqrt(x * y) for the first deposit
+        if lp_supply == 0 {
+            let lp_minted =
+                amm_math::initial_liquidity(amount_a, amount_b).map_err(AMMError::from)?;
+            return Ok(Deposit {
+                amount_a,
+                amount_b,
+                lp_minted,
+            });
+        }
+
+        let deposit = amm_math::deposit_shares(
+            amount_a,
+            amount_b,
+            pool.reserve_a,
+            pool.reserve_b,
+            lp_supply,
+        );
+        Ok(deposit.map_err(AMMError::from)?)
+    }
+
+    fn withdraw_amounts(&self, pool: &Pool, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal> {
+        proportional_withdrawal(pool, lp_amount, lp_supply)
+    }
+
+    fn spot_price(&self, pool: &Pool, _now: i64) -> Result<u128> {
+        let price = amm_math::spot_price(pool.reserve_a, pool.reserve_b);
+        Ok(price.map_err(AMMError::from)?)
+    }
+}
+
+impl SwapCurve for StableSwapCurve {
+    fn validate(&self, pool: &Pool) -> Result<()> {
+        require!(
+            (MIN_AMP..=MAX_AMP).contains(&pool.target_amp),
+            AMMError::InvalidAmp
+        );
+        require!(
+            pool.weight_a_bps == 0 && pool.tick_spacing == 0,
+            AMMError::InvalidWeights
+        );
+        Ok(())
+    }
+
+    fn swap_exact_in(&self, pool: &Pool, amount_in: u64, a_to_b: bool) -> Result<u64> {
+        let ((reserve_in, _), (reserve_out, _)) = sides(pool, a_to_b);
+        let amount_out = amm_math::stable_swap_exact_in(
+            amp(pool)?,
+            reserve_in,
+            reserve_out,
+            amount_in,
+            pool.fee_bps,
+        );
+        Ok(amount_out.map_err(AMMError::from)?)
+    }
+
+    fn swap_exact_out(&self, pool: &Pool, amount_out: u64, a_to_b: bool) -> Result<u64> {
+        let ((reserve_in, _), (reserve_out, _)) = sides(pool, a_to_b);
+        let amount_in = amm_math::stable_swap_exact_out(
+            amp(pool)?,
+            reserve_in,
+            reserve_out,
+            amount_out,
+            pool.fee_bps,
+        );
+        Ok(amount_in.map_err(AMMError::from)?)
+    }
+
+    fn deposit_shares(
+        &self,
+        pool: &Pool,
+        amount_a: u64,
+        amount_b: u64,
+        lp_supply: u64,
+    ) -> Result<Deposit> {
+        // D of the amounts for the first deposit
+        if lp_supply == 0 {
+            let lp_minted = amm_math::stable_initial_liquidity(amp(pool)?, amount_a, amount_b)
+                .map_err(AMMError::from)?;
+            return Ok(Deposit {
+                amount_a,
+                amount_b,
+                lp_minted,
+            });
+        }
+
+        let deposit = amm_math::stable_deposit_shares(
+            amp(pool)?,
+            amount_a,
+            amount_b,
+            pool.reserve_a,
+            pool.reserve_b,
+            lp_supply,
+            pool.fee_bps,
+        );
+        Ok(deposit.map_err(AMMError::from)?)
+    }
+
+    fn withdraw_amounts(&self, pool: &Pool, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal> {
+        proportional_withdrawal(pool, lp_amount, lp_supply)
+    }
+
+    fn spot_price(&self, pool: &Pool, now: i64) -> Result<u128> {
+        let price =
+            amm_math::stable_spot_price(pool.current_amp(now), pool.reserve_a, pool.reserve_b);
+        Ok(price.map_err(AMMError::from)?)
+    }
+}
+
+impl SwapCurve for WeightedCurve {
+    fn validate(&self, pool: &Pool) -> Result<()> {
+        require!(
+            pool.target_amp == 0
+                && pool.tick_spacing == 0
+                && pool.weight_a_bps as u64 + pool.weight_b_bps as u64 == BPS_DENOMINATOR
+                && pool.weight_a_bps.min(pool.weight_b_bps) >= MIN_WEIGHT_BPS,
+            AMMError::InvalidWeights
+        );
+        Ok(())
+    }
+
+    fn swap_exact_in(&self, pool: &Pool, amount_in: u64, a_to_b: bool) -> Result<u64> {
+        let ((reserve_in, weight_in), (reserve_out, weight_out)) = sides(pool, a_to_b);
+        let amount_out = amm_math::weighted_swap_exact_in(
+            reserve_in,
+            weight_in,
+            reserve_out,
+            weight_out,
+            amount_in,
+            pool.fee_bps,
+        );
+        Ok(amount_out.map_err(AMMError::from)?)
+    }
+
+    fn swap_exact_out(&self, pool: &Pool, amount_out: u64, a_to_b: bool) -> Result<u64> {
+        let ((reserve_in, weight_in), (reserve_out, weight_out)) = sides(pool, a_to_b);
+        let amount_in = amm_math::weighted_swap_exact_out(
+            reserve_in,
+            weight_in,
+            reserve_out,
+            weight_out,
+            amount_out,
+            pool.fee_bps,
+        );
+        Ok(amount_in.map_err(AMMError::from)?)
+    }
+
+    // Proportional deposits keep the weights, they share the constant product math after the first one
+    fn deposit_shares(
+        &self,
+        pool: &Pool,
+        amount_a: u64,
+        amount_b: u64,
+        lp_supply: u64,
+    ) -> Result<Deposit> {
+        // x^w_a * y^w_b for the first deposit
+        if lp_supply == 0 {
+            let lp_minted = amm_math::weighted_initial_liquidity(
+                amount_a,
+                pool.weight_a_bps,
+                amount_b,
+                pool.weight_b_bps,
+            )
+            .map_err(AMMError::from)?;
+            return Ok(Deposit {
+                amount_a,
+                amount_b,
+                lp_minted,
+            });
+        }
+
+        ConstantProductCurve.deposit_shares(pool, amount_a, amount_b, lp_supply)
+    }
+
+    fn withdraw_amounts(&self, pool: &Pool, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal> {
+        proportional_withdrawal(pool, lp_amount, lp_supply)
+    }
+
+    fn spot_price(&self, pool: &Pool, _now: i64) -> Result<u128> {
+        let price = amm_math::weighted_spot_price(
+            pool.reserve_a,
+            pool.weight_a_bps,
+            pool.reserve_b,
+            pool.weight_b_bps,
+        );
+        Ok(price.map_err(AMMError::from)?)
+    }
+}
+
+// Concentrated liquidity pools swap across the ticks of their tick arrays (swap_concentrated) and hold their
+// liquidity in positions, nothing here can be priced from the pool alone but the current price
+impl SwapCurve for ConcentratedCurve {
+    fn validate(&self, pool: &Pool) -> Result<()> {
+        require!(
+            (1..=MAX_TICK_SPACING).contains(&pool.tick_spacing),
+            AMMError::InvalidTickSpacing
+        );
+        require!(
+            (MIN_SQRT_PRICE..MAX_SQRT_PRICE).contains(&pool.sqrt_price),
+            AMMError::InvalidSqrtPrice
+        );
+        require!(
+            pool.target_amp == 0 && pool.weight_a_bps == 0,
+            AMMError::InvalidInput
+        );
+        Ok(())
+    }
+
+    fn swap_exact_in(&self, _pool: &Pool, _amount_in: u64, _a_to_b: bool) -> Result<u64> {
+        err!(AMMError::WrongPoolType)
+    }
+
+    fn swap_exact_out(&self, _pool: &Pool, _amount_out: u64, _a_to_b: bool) -> Result<u64> {
+        err!(AMMError::WrongPoolType)
+    }
+
+    fn deposit_shares(
+        &self,
+        _pool: &Pool,
+        _amount_a: u64,
+        _amount_b: u64,
+        _lp_supply: u64,
+    ) -> Result<Deposit> {
+        err!(AMMError::WrongPoolType)
+    }
+
+    fn withdraw_amounts(
+        &self,
+        _pool: &Pool,
+        _lp_amount: u64,
+        _lp_supply: u64,
+    ) -> Result<Withdrawal> {
+        err!(AMMError::WrongPoolType)
+    }
+
+    fn spot_price(&self, pool: &Pool, _now: i64) -> Result<u128> {
+        let price = amm_math::concentrated_spot_price(pool.sqrt_price);
+        Ok(price.map_err(AMMError::from)?)
+    }
+}
+
+// Dispatch on the curve type stored on the pool
+impl SwapCurve for CurveType {
+    fn validate(&self, pool: &Pool) -> Result<()> {
+        match self {
+            CurveType::ConstantProduct => ConstantProductCurve.validate(pool),
+            CurveType::StableSwap => StableSwapCurve.validate(pool),
+            CurveType::Weighted => WeightedCurve.validate(pool),
+            CurveType::Concentrated => ConcentratedCurve.validate(pool),
+        }
+    }
+
+    fn swap_exact_in(&self, pool: &Pool, amount_in: u64, a_to_b: bool) -> Result<u64> {
+        match self {
+            CurveType::ConstantProduct => {
+                ConstantProductCurve.swap_exact_in(pool, amount_in, a_to_b)
+            }
+            CurveType::StableSwap => StableSwapCurve.swap_exact_in(pool, amount_in, a_to_b),
+            CurveType::Weighted => WeightedCurve.swap_exact_in(pool, amount_in, a_to_b),
+            CurveType::Concentrated => ConcentratedCurve.swap_exact_in(pool, amount_in, a_to_b),
+        }
+    }
+
+    fn swap_exact_out(&self, pool: &Pool, amount_out: u64, a_to_b: bool) -> Result<u64> {
+        match self {
+            CurveType::ConstantProduct => {
+                ConstantProductCurve.swap_exact_out(pool, amount_out, a_to_b)
+            }
+            CurveType::StableSwap => StableSwapCurve.swap_exact_out(pool, amount_out, a_to_b),
+            CurveType::Weighted => WeightedCurve.swap_exact_out(pool, amount_out, a_to_b),
+            CurveType::Concentrated => ConcentratedCurve.swap_exact_out(pool, amount_out, a_to_b),
+        }
+    }
+
+    fn deposit_shares(
+        &self,
+        pool: &Pool,
+        amount_a: u64,
+        amount_b: u64,
+        lp_supply: u64,
+    ) -> Result<Deposit> {
+        match self {
+            CurveType::ConstantProduct => {
+                ConstantProductCurve.deposit_shares(pool, amount_a, amount_b, lp_supply)
+            }
+            CurveType::StableSwap => {
+                StableSwapCurve.deposit_shares(pool, amount_a, amount_b, lp_supply)
+            }
+            CurveType::Weighted => {
+                WeightedCurve.deposit_shares(pool, amount_a, amount_b, lp_supply)
+            }
+            CurveType::Concentrated => {
+                ConcentratedCurve.deposit_shares(pool, amount_a, amount_b, lp_supply)
+            }
+        }
+    }
+
+    fn withdraw_amounts(&self, pool: &Pool, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal> {
+        match self {
+            CurveType::ConstantProduct => {
+                ConstantProductCurve.withdraw_amounts(pool, lp_amount, lp_supply)
+            }
+            CurveType::StableSwap => StableSwapCurve.withdraw_amounts(pool, lp_amount, lp_supply),
+            CurveType::Weighted => WeightedCurve.withdraw_amounts(pool, lp_amount, lp_supply),
+            CurveType::Concentrated => {
+                ConcentratedCurve.withdraw_amounts(pool, lp_amount, lp_supply)
+            }
+        }
+    }
+
+    fn spot_price(&self, pool: &Pool, now: i64) -> Result<u128> {
+        match self {
+            CurveType::ConstantProduct => ConstantProductCurve.spot_price(pool, now),
+            CurveType::StableSwap => StableSwapCurve.spot_price(pool, now),
+            CurveType::Weighted => WeightedCurve.spot_price(pool, now),
+            CurveType::Concentrated => ConcentratedCurve.spot_price(pool, now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(curve_type: CurveType) -> Pool {
+        Pool {
+            token_a_mint: Pubkey::default(),
+            token_b_mint: Pubkey::default(),
+            vault_a: Pubkey::default(),
+            vault_b: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            bump: 0,
+            fee_bps: 30,
+            protocol_fees_a: 0,
+            protocol_fees_b: 0,
+            status: 0,
+            reserve_a: 1_000_000,
+            reserve_b: 4_000_000,
+            curve_type,
+            initial_amp: 0,
+            target_amp: 0,
+            ramp_start_ts: 0,
+            ramp_stop_ts: 0,
+            weight_a_bps: 0,
+            weight_b_bps: 0,
+            tick_spacing: 0,
+            sqrt_price: 0,
+            tick_current: 0,
+            liquidity: 0,
+            fee_growth_global_a: 0,
+            fee_growth_global_b: 0,
+        }
+    }
+
+    #[test]
+    fn curves_validate_their_parameters() {
+        assert!(pool(CurveType::ConstantProduct).validate().is_ok());
+
+        let mut stable = pool(CurveType::StableSwap);
+        assert_eq!(stable.validate().unwrap_err(), AMMError::InvalidAmp.into());
+        stable.target_amp = 100;
+        assert!(stable.validate().is_ok());
+        stable.weight_a_bps = 5_000;
+        assert_eq!(
+            stable.validate().unwrap_err(),
+            AMMError::InvalidWeights.into()
+        );
+
+        let mut weighted = pool(CurveType::Weighted);
+        (weighted.weight_a_bps, weighted.weight_b_bps) = (8_000, 2_000);
+        assert!(weighted.validate().is_ok());
+        (weighted.weight_a_bps, weighted.weight_b_bps) = (9_950, 50);
+        assert_eq!(
+            weighted.validate().unwrap_err(),
+            AMMError::InvalidWeights.into()
+        );
+
+        let mut concentrated = pool(CurveType::Concentrated);
+        concentrated.sqrt_price = 1 << 64;
+        assert_eq!(
+            concentrated.validate().unwrap_err(),
+            AMMError::InvalidTickSpacing.into()
+        );
+        concentrated.tick_spacing = 10;
+        assert!(concentrated.validate().is_ok());
+    }
+
+    #[test]
+    fn pools_price_on_their_curve_type() {
+        let constant_product = pool(CurveType::ConstantProduct);
+        assert_eq!(
+            constant_product.swap_exact_in(10_000, true).unwrap(),
+            amm_math::swap_exact_in(1_000_000, 4_000_000, 10_000, 30).unwrap()
+        );
+        assert_eq!(constant_product.spot_price(0).unwrap(), 4 << 64);
+        assert_eq!(
+            constant_product.deposit(1_000, 4_000, 0).unwrap().lp_minted,
+            2_000
+        );
+
+        // Same reserves at 80/20 put 4x the value of the B reserve in A
+        let mut weighted = pool(CurveType::Weighted);
+        (weighted.weight_a_bps, weighted.weight_b_bps) = (8_000, 2_000);
+        assert_eq!(weighted.spot_price(0).unwrap(), 16 << 64);
+        assert_eq!(
+            weighted.withdraw(1_000, 10_000).unwrap(),
+            constant_product.withdraw(1_000, 10_000).unwrap()
+        );
+
+        // A ramping amp is read at the timestamp the price is asked for
+        let mut stable = pool(CurveType::StableSwap);
+        (stable.initial_amp, stable.target_amp) = (100, 200);
+        (stable.ramp_start_ts, stable.ramp_stop_ts) = (0, 100);
+        assert_eq!(
+            stable.spot_price(50).unwrap(),
+            amm_math::stable_spot_price(150, 1_000_000, 4_000_000).unwrap()
+        );
+        assert_ne!(
+            stable.spot_price(50).unwrap(),
+            stable.spot_price(100).unwrap()
+        );
+
+        let mut concentrated = pool(CurveType::Concentrated);
+        concentrated.sqrt_price = 2 << 64;
+        assert_eq!(concentrated.spot_price(0).unwrap(), 4 << 64);
+        assert_eq!(
+            concentrated.swap_exact_in(10_000, true).unwrap_err(),
+            AMMError::WrongPoolType.into()
+        );
+        assert_eq!(
+            concentrated.deposit(1_000, 4_000, 0).unwrap_err(),
+            AMMError::WrongPoolType.into()
+        );
+    }
+}
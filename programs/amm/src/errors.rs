@@ -1,3 +1,4 @@
+use amm_math::MathError;
 use anchor_lang::prelude::*;
 
 #[error_code]
@@ -16,4 +17,73 @@ pub enum AMMError {
     ZeroContriInPool,
     #[msg("Amount recieved has become less than the minimum tokens requirement")]
     SlippageExceededForLR,
+    #[msg("The fee tier provided is not supported")]
+    InvalidFeeTier,
+    #[msg("There are no protocol fees to collect")]
+    NoProtocolFees,
+    #[msg("The signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("The protocol fee provided is not valid")]
+    InvalidProtocolFee,
+    #[msg("This operation is currently halted")]
+    OperationHalted,
+    #[msg("The status provided is not valid")]
+    InvalidStatus,
+    #[msg("The deadline for this transaction has passed")]
+    Expired,
+    #[msg("The initial liquidity provided is below the minimum liquidity")]
+    InsufficientInitialLiquidity,
+    #[msg("Math operation overflowed")]
+    MathOverflow,
+    #[msg("Math operation divided by zero")]
+    DivisionByZero,
+    #[msg("The pool has no liquidity")]
+    EmptyPool,
+    #[msg("Token A and Token B of a pool have to be different mints")]
+    IdenticalMints,
+    #[msg("Token A mint has to be the lower pubkey of the pair")]
+    InvalidMintOrder,
+    #[msg("The amplification coefficient provided is not valid")]
+    InvalidAmp,
+    #[msg("Token A and Token B of a stable pool have to share their decimals")]
+    MismatchedDecimals,
+    #[msg("The curve invariant did not converge")]
+    NoConvergence,
+    #[msg("The pool is not a stable pool")]
+    NotStablePool,
+    #[msg("The amplification coefficient changes too fast")]
+    AmpChangeTooFast,
+    #[msg("The amplification coefficient was ramped too recently")]
+    RampTooSoon,
+    #[msg("The weights provided are not valid")]
+    InvalidWeights,
+    #[msg("The swap is too large a share of the pool's reserves")]
+    MaxRatioExceeded,
+    #[msg("This operation is not supported by this type of pool")]
+    WrongPoolType,
+    #[msg("The tick spacing provided is not valid")]
+    InvalidTickSpacing,
+    #[msg("The square root price provided is not valid")]
+    InvalidSqrtPrice,
+    #[msg("The ticks provided are not valid")]
+    InvalidTick,
+    #[msg("The tick array provided is not valid")]
+    InvalidTickArray,
+    #[msg("The tick arrays needed by this operation were not provided")]
+    TickArrayMissing,
+}
+
+impl From<MathError> for AMMError {
+    fn from(error: MathError) -> Self {
+        match error {
+            MathError::Overflow => AMMError::MathOverflow,
+            MathError::DivisionByZero => AMMError::DivisionByZero,
+            MathError::EmptyPool => AMMError::EmptyPool,
+            MathError::InsufficientLiquidity => AMMError::InsufficientLiquidity,
+            MathError::InvalidInput => AMMError::InvalidInput,
+            MathError::InsufficientAmount => AMMError::InsufficientAmount,
+            MathError::NoConvergence => AMMError::NoConvergence,
+            MathError::MaxRatioExceeded => AMMError::MaxRatioExceeded,
+        }
+    }
 }
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PoolCreated {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub fee_bps: u16,
+    // Amplification coefficient of a StableSwap pool, 0 for a constant product pool
+    pub amp: u64,
+    // Weight of token A (in basis points) of a weighted pool, 0 otherwise
+    pub weight_a_bps: u16,
+    // Tick spacing and initial square root price (Q64.64) of a concentrated liquidity pool, 0 otherwise
+    pub tick_spacing: u16,
+    pub sqrt_price: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    // LP tokens minted to the creator, MINIMUM_LIQUIDITY is locked on top of it
+    pub lp_minted: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct Swapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub a_to_b: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    // Swap fee taken from amount_in, protocol_fee is the part of it owed to the treasury
+    pub fee: u64,
+    pub protocol_fee: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_minted: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_burned: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct ReservesSynced {
+    pub pool: Pubkey,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+}
+
+#[event]
+pub struct Skimmed {
+    pub pool: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct StatusChanged {
+    // None when the global status on the config changed
+    pub pool: Option<Pubkey>,
+    pub authority: Pubkey,
+    pub status: u8,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub fee_tiers: [u16; 4],
+    pub protocol_fee_bps: u16,
+    pub treasury: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct AmpRamped {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    // The amp goes linearly from initial_amp to target_amp between the two timestamps, equal when stopped
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_ts: i64,
+    pub ramp_stop_ts: i64,
+}
+
+#[event]
+pub struct PositionUpdated {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    // Positive when liquidity was added and the amounts paid in, negative when removed and the amounts paid
+    // out along with the fees earned by the position
+    pub liquidity_delta: i128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fees_a: u64,
+    pub fees_b: u64,
+    pub liquidity: u128,
+    pub pool_liquidity: u128,
+}
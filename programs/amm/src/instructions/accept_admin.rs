@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+use crate::state::AmmConfig;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        constraint = amm_config.pending_admin == Some(pending_admin.key()) @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.admin = ctx.accounts.pending_admin.key();
+    amm_config.pending_admin = None;
+
+    emit!(ctx.accounts.amm_config.to_event());
+
+    Ok(())
+}
@@ -1,34 +1,44 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked}};
 
-use crate::state::Pool;
+use crate::state::{AmmConfig, Pool};
+use crate::constants::STATUS_DEPOSIT_DISABLED;
 use crate::errors::AMMError;
+use crate::events::LiquidityAdded;
+use crate::math::SafeMath;
+use crate::utils::check_deadline;
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
     #[account(mut)]
     pub liquidity_provider: Signer<'info>,
 
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
     pub token_a_mint: InterfaceAccount<'info, Mint>,
 
     pub token_b_mint: InterfaceAccount<'info,Mint>,
 
     #[account(
         mut,
-        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
         bump = liquidity_pool.bump,
     )]
     pub liquidity_pool: Account<'info, Pool>,
 
     #[account(
         mut,
-        seeds = [b"vault_a", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_a : InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [b"vault_b", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_b: InterfaceAccount<'info, TokenAccount>,
@@ -53,7 +63,7 @@ pub struct AddLiquidity<'info> {
 
     #[account(
         mut,
-        seeds = [b"lp_mint", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"lp_mint", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub lp_mint : InterfaceAccount<'info, Mint>,
@@ -73,33 +83,50 @@ pub struct AddLiquidity<'info> {
 }
 
 
+// Returned to the caller through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AddLiquidityResult {
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_minted: u64,
+}
+
+impl From<amm_math::Deposit> for AddLiquidityResult {
+    fn from(deposit: amm_math::Deposit) -> Self {
+        Self { amount_a: deposit.amount_a, amount_b: deposit.amount_b, lp_minted: deposit.lp_minted }
+    }
+}
+
 pub fn process_add_liquidity(
     ctx: Context<AddLiquidity>,
     amount_a: u64,
     amount_b: u64,
     minimum_lp_tokens: u64,
-) -> Result<()> {
-
-    // Firstly , Lets store the state 
-    let vault_a_amount = ctx.accounts.vault_a.amount;
-    let vault_b_amount = ctx.accounts.vault_b.amount;
+    deadline: Option<i64>,
+) -> Result<AddLiquidityResult> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the deposits being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_DEPOSIT_DISABLED),
+        AMMError::OperationHalted
+    );
+
+    // Firstly , Lets store the state (reserves tracked by the pool)
+    let vault_a_amount = ctx.accounts.liquidity_pool.reserve_a;
+    let vault_b_amount = ctx.accounts.liquidity_pool.reserve_b;
     let total_lp_supply = ctx.accounts.lp_mint.supply;
 
-    // Calculation of the lp needed to mint
-    // required lp_tokens = amount_provided_for_pool / total amount in liquidity_pool's vault * total_supply of lp_tokens
-    let required_lp_token_a = (amount_a as u128).checked_mul(total_lp_supply as u128).unwrap().checked_div(vault_a_amount as u128).unwrap();
-    let required_lp_token_b = (amount_b as u128).checked_mul(total_lp_supply as u128).unwrap().checked_div(vault_b_amount as u128).unwrap();
-
-    let lp_token_to_mint = std::cmp::min(required_lp_token_a, required_lp_token_b);
-
-    require!(lp_token_to_mint >= minimum_lp_tokens as u128, AMMError::SlippageExceededForLP);
+    // Calculate the lp tokens to mint and the amounts to transfer for them, following the pool's curve
+    let deposit = ctx.accounts.liquidity_pool.deposit(amount_a, amount_b, total_lp_supply)?;
+    let lp_token_to_mint = deposit.lp_minted;
+    let amount_to_transfer_a = deposit.amount_a;
+    let amount_to_transfer_b = deposit.amount_b;
 
-    // Calculate the amount required for transfer for the lp_token calculated
-    let amount_to_transfer_a = (lp_token_to_mint).checked_mul(vault_a_amount as u128).unwrap().checked_div(total_lp_supply as u128).unwrap() as u64;
-    let amount_to_transfer_b = (lp_token_to_mint).checked_mul(vault_b_amount as u128).unwrap().checked_div(total_lp_supply as u128).unwrap() as u64;
-
-    // Security check for user providing enough amount of both tokens
-    require!(amount_a >= amount_to_transfer_a && amount_b >= amount_to_transfer_b , AMMError::InsufficientAmount);
+    require!(lp_token_to_mint >= minimum_lp_tokens, AMMError::SlippageExceededForLP);
 
     // Transfer the amount to the specific liquidity-pool vaults 
     let transfer_a_accounts = TransferChecked{
@@ -131,11 +158,27 @@ pub fn process_add_liquidity(
     };
     let mint_a_keys = ctx.accounts.token_a_mint.key();
     let mint_b_keys = ctx.accounts.token_b_mint.key();
-    let signer_seeds: &[&[&[u8]]] = &[&[b"pool", mint_a_keys.as_ref(), mint_b_keys.as_ref(), &[ctx.accounts.liquidity_pool.bump]]];
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"pool", mint_a_keys.as_ref(), mint_b_keys.as_ref(), fee_bps_keys.as_ref(), &[ctx.accounts.liquidity_pool.curve_type as u8], &[ctx.accounts.liquidity_pool.bump]]];
     let cpi_ctx_lp = CpiContext::new(ctx.accounts.token_program.to_account_info(), mint_to_accounts).with_signer(signer_seeds);
 
-    token_interface::mint_to(cpi_ctx_lp, lp_token_to_mint as u64)?;
-
-
-    Ok(())
+    token_interface::mint_to(cpi_ctx_lp, lp_token_to_mint)?;
+
+    // Update the reserves with the deposited amounts
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.reserve_a = vault_a_amount.safe_add(amount_to_transfer_a)?;
+    pool.reserve_b = vault_b_amount.safe_add(amount_to_transfer_b)?;
+
+    emit!(LiquidityAdded {
+        pool: pool.key(),
+        user: ctx.accounts.liquidity_provider.key(),
+        amount_a: amount_to_transfer_a,
+        amount_b: amount_to_transfer_b,
+        lp_minted: lp_token_to_mint,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+        lp_supply: total_lp_supply.safe_add(lp_token_to_mint)?,
+    });
+
+    Ok(deposit.into())
 }
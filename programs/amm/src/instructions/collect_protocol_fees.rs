@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::errors::AMMError;
+use crate::events::ProtocolFeesCollected;
+use crate::state::{AmmConfig, Pool};
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(mut)]
+    pub collector: Signer<'info>,
+
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Checked against the treasury stored on the config
+    #[account(address = amm_config.treasury @ AMMError::Unauthorized)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = collector,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = collector,
+        associated_token::mint = token_b_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn process_collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    let protocol_fees_a = ctx.accounts.liquidity_pool.protocol_fees_a;
+    let protocol_fees_b = ctx.accounts.liquidity_pool.protocol_fees_b;
+
+    require!(
+        protocol_fees_a > 0 || protocol_fees_b > 0,
+        AMMError::NoProtocolFees
+    );
+
+    // Reset the accrued fees before moving the tokens out of the vaults
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    let mint_a_keys = ctx.accounts.token_a_mint.key();
+    let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"pool",
+        mint_a_keys.as_ref(),
+        mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[ctx.accounts.liquidity_pool.curve_type as u8],
+        &[ctx.accounts.liquidity_pool.bump],
+    ]];
+
+    // Transfer the accrued fees from the vaults to the treasury
+    if protocol_fees_a > 0 {
+        let transfer_a_accounts = TransferChecked {
+            from: ctx.accounts.vault_a.to_account_info(),
+            to: ctx.accounts.treasury_token_a.to_account_info(),
+            mint: ctx.accounts.token_a_mint.to_account_info(),
+            authority: ctx.accounts.liquidity_pool.to_account_info(),
+        };
+        let cpi_ctx_a = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_a_accounts,
+            signer_seeds,
+        );
+        let decimals_a = ctx.accounts.token_a_mint.decimals;
+        token_interface::transfer_checked(cpi_ctx_a, protocol_fees_a, decimals_a)?;
+    }
+
+    if protocol_fees_b > 0 {
+        let transfer_b_accounts = TransferChecked {
+            from: ctx.accounts.vault_b.to_account_info(),
+            to: ctx.accounts.treasury_token_b.to_account_info(),
+            mint: ctx.accounts.token_b_mint.to_account_info(),
+            authority: ctx.accounts.liquidity_pool.to_account_info(),
+        };
+        let cpi_ctx_b = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_b_accounts,
+            signer_seeds,
+        );
+        let decimals_b = ctx.accounts.token_b_mint.decimals;
+        token_interface::transfer_checked(cpi_ctx_b, protocol_fees_b, decimals_b)?;
+    }
+
+    emit!(ProtocolFeesCollected {
+        pool: ctx.accounts.liquidity_pool.key(),
+        treasury: ctx.accounts.treasury.key(),
+        amount_a: protocol_fees_a,
+        amount_b: protocol_fees_b,
+    });
+
+    Ok(())
+}
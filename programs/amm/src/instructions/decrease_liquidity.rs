@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TransferChecked};
+
+use crate::concentrated::{update_position, TickArrays};
+use crate::constants::STATUS_WITHDRAW_DISABLED;
+use crate::errors::AMMError;
+use crate::events::PositionUpdated;
+use crate::instructions::increase_liquidity::{ModifyLiquidity, PositionAmounts};
+use crate::math::SafeMath;
+use crate::utils::check_deadline;
+
+pub fn process_decrease_liquidity(
+    ctx: Context<ModifyLiquidity>,
+    liquidity: u128,
+    minimum_token_a: u64,
+    minimum_token_b: u64,
+    deadline: Option<i64>,
+) -> Result<PositionAmounts> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the withdrawals being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_WITHDRAW_DISABLED),
+        AMMError::OperationHalted
+    );
+    require!(
+        ctx.accounts.liquidity_pool.is_concentrated(),
+        AMMError::WrongPoolType
+    );
+    require!(
+        liquidity <= ctx.accounts.position.liquidity,
+        AMMError::InsufficientAmount
+    );
+    let liquidity_delta = -i128::try_from(liquidity).map_err(|_| AMMError::MathOverflow)?;
+
+    // Remove the liquidity from the position, its ticks and the pool, a zero liquidity only collects the fees
+    let tick_array_accounts = [
+        ctx.accounts.tick_array_lower.to_account_info(),
+        ctx.accounts.tick_array_upper.to_account_info(),
+    ];
+    let mut tick_arrays = TickArrays::load(&ctx.accounts.liquidity_pool, &tick_array_accounts)?;
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let position = &mut ctx.accounts.position;
+    update_position(pool, position, &mut tick_arrays, liquidity_delta)?;
+    tick_arrays.save()?;
+
+    // Amounts backing the liquidity at the current price, rounded down in favor of the pool
+    let (amount_a, amount_b) = amm_math::amounts_for_liquidity(
+        pool.sqrt_price,
+        amm_math::sqrt_price_at_tick(position.tick_lower).map_err(AMMError::from)?,
+        amm_math::sqrt_price_at_tick(position.tick_upper).map_err(AMMError::from)?,
+        liquidity,
+        false,
+    )
+    .map_err(AMMError::from)?;
+
+    require!(
+        amount_a >= minimum_token_a && amount_b >= minimum_token_b,
+        AMMError::SlippageExceededForLR
+    );
+
+    // The fees earned by the position are paid out with the liquidity
+    let (fees_a, fees_b) = (position.tokens_owed_a, position.tokens_owed_b);
+    position.tokens_owed_a = 0;
+    position.tokens_owed_b = 0;
+    let total_a = amount_a.safe_add(fees_a)?;
+    let total_b = amount_b.safe_add(fees_b)?;
+
+    // Transfer the tokens from the vaults to the owner
+    let mint_a_keys = ctx.accounts.token_a_mint.key();
+    let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"pool",
+        mint_a_keys.as_ref(),
+        mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[ctx.accounts.liquidity_pool.curve_type as u8],
+        &[ctx.accounts.liquidity_pool.bump],
+    ]];
+
+    let transfer_a_accounts = TransferChecked {
+        from: ctx.accounts.vault_a.to_account_info(),
+        to: ctx.accounts.owner_token_a.to_account_info(),
+        mint: ctx.accounts.token_a_mint.to_account_info(),
+        authority: ctx.accounts.liquidity_pool.to_account_info(),
+    };
+    let cpi_ctx_a = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_a_accounts,
+        signer_seeds,
+    );
+    token_interface::transfer_checked(cpi_ctx_a, total_a, ctx.accounts.token_a_mint.decimals)?;
+
+    let transfer_b_accounts = TransferChecked {
+        from: ctx.accounts.vault_b.to_account_info(),
+        to: ctx.accounts.owner_token_b.to_account_info(),
+        mint: ctx.accounts.token_b_mint.to_account_info(),
+        authority: ctx.accounts.liquidity_pool.to_account_info(),
+    };
+    let cpi_ctx_b = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_b_accounts,
+        signer_seeds,
+    );
+    token_interface::transfer_checked(cpi_ctx_b, total_b, ctx.accounts.token_b_mint.decimals)?;
+
+    // Update the reserves with the withdrawn amounts
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.reserve_a = pool.reserve_a.safe_sub(total_a)?;
+    pool.reserve_b = pool.reserve_b.safe_sub(total_b)?;
+
+    emit!(PositionUpdated {
+        pool: pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        liquidity_delta,
+        amount_a,
+        amount_b,
+        fees_a,
+        fees_b,
+        liquidity: ctx.accounts.position.liquidity,
+        pool_liquidity: pool.liquidity,
+    });
+
+    Ok(PositionAmounts {
+        amount_a: total_a,
+        amount_b: total_b,
+    })
+}
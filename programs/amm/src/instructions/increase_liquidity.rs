@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::concentrated::{update_position, TickArrays};
+use crate::constants::STATUS_DEPOSIT_DISABLED;
+use crate::errors::AMMError;
+use crate::events::PositionUpdated;
+use crate::math::SafeMath;
+use crate::state::{AmmConfig, Pool, Position};
+use crate::utils::check_deadline;
+
+#[derive(Accounts)]
+pub struct ModifyLiquidity<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        has_one = owner @ AMMError::Unauthorized,
+        seeds = [b"position", liquidity_pool.key().as_ref(), owner.key().as_ref(), position.tick_lower.to_le_bytes().as_ref(), position.tick_upper.to_le_bytes().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// CHECK: Tick array holding the position's lower tick, loaded and checked against the pool by TickArrays
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: Tick array holding the position's upper tick, can be the same account as tick_array_lower
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_b_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Returned to the caller through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PositionAmounts {
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+pub fn process_increase_liquidity(
+    ctx: Context<ModifyLiquidity>,
+    liquidity: u128,
+    maximum_token_a: u64,
+    maximum_token_b: u64,
+    deadline: Option<i64>,
+) -> Result<PositionAmounts> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the deposits being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_DEPOSIT_DISABLED),
+        AMMError::OperationHalted
+    );
+    require!(
+        ctx.accounts.liquidity_pool.is_concentrated(),
+        AMMError::WrongPoolType
+    );
+    require!(liquidity > 0, AMMError::InvalidInput);
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| AMMError::MathOverflow)?;
+
+    // Add the liquidity to the position, its ticks and the pool if it's in range
+    let tick_array_accounts = [
+        ctx.accounts.tick_array_lower.to_account_info(),
+        ctx.accounts.tick_array_upper.to_account_info(),
+    ];
+    let mut tick_arrays = TickArrays::load(&ctx.accounts.liquidity_pool, &tick_array_accounts)?;
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let position = &mut ctx.accounts.position;
+    update_position(pool, position, &mut tick_arrays, liquidity_delta)?;
+    tick_arrays.save()?;
+
+    // Amounts backing the liquidity at the current price, rounded up in favor of the pool
+    let (amount_a, amount_b) = amm_math::amounts_for_liquidity(
+        pool.sqrt_price,
+        amm_math::sqrt_price_at_tick(position.tick_lower).map_err(AMMError::from)?,
+        amm_math::sqrt_price_at_tick(position.tick_upper).map_err(AMMError::from)?,
+        liquidity,
+        true,
+    )
+    .map_err(AMMError::from)?;
+
+    require!(
+        amount_a <= maximum_token_a && amount_b <= maximum_token_b,
+        AMMError::SlippageExceeded
+    );
+
+    // Transfer the amounts to the pool's vaults
+    let transfer_a_accounts = TransferChecked {
+        from: ctx.accounts.owner_token_a.to_account_info(),
+        to: ctx.accounts.vault_a.to_account_info(),
+        mint: ctx.accounts.token_a_mint.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_ctx_a = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_a_accounts,
+    );
+    token_interface::transfer_checked(cpi_ctx_a, amount_a, ctx.accounts.token_a_mint.decimals)?;
+
+    let transfer_b_accounts = TransferChecked {
+        from: ctx.accounts.owner_token_b.to_account_info(),
+        to: ctx.accounts.vault_b.to_account_info(),
+        mint: ctx.accounts.token_b_mint.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_ctx_b = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_b_accounts,
+    );
+    token_interface::transfer_checked(cpi_ctx_b, amount_b, ctx.accounts.token_b_mint.decimals)?;
+
+    // The reserves of a concentrated liquidity pool are what the positions own, their fees included
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.reserve_a = pool.reserve_a.safe_add(amount_a)?;
+    pool.reserve_b = pool.reserve_b.safe_add(amount_b)?;
+
+    emit!(PositionUpdated {
+        pool: pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        liquidity_delta,
+        amount_a,
+        amount_b,
+        fees_a: 0,
+        fees_b: 0,
+        liquidity: ctx.accounts.position.liquidity,
+        pool_liquidity: pool.liquidity,
+    });
+
+    Ok(PositionAmounts { amount_a, amount_b })
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::AMMError;
+use crate::events::PoolCreated;
+use crate::state::{AmmConfig, CurveType, Pool};
+
+#[derive(Accounts)]
+#[instruction(fee_bps: u16)]
+pub struct InitializeConcentratedPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = token_a_mint,
+        token::authority = liquidity_pool,
+        token::token_program = token_program,
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = token_b_mint,
+        token::authority = liquidity_pool,
+        token::token_program = token_program,
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), fee_bps.to_le_bytes().as_ref(), &[CurveType::Concentrated as u8]],
+        bump,
+    )]
+    pub liquidity_pool: Box<Account<'info, Pool>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn process_initialize_concentrated_pool(
+    ctx: Context<InitializeConcentratedPool>,
+    fee_bps: u16,
+    tick_spacing: u16,
+    sqrt_price: u128,
+) -> Result<()> {
+    // Token A has to be the lower pubkey of the two distinct mints, so a pair can't be split across A/B
+    // and B/A pools. It still gets one pool per fee tier and curve
+    let mint_a_key = ctx.accounts.token_a_mint.key();
+    let mint_b_key = ctx.accounts.token_b_mint.key();
+    require_keys_neq!(mint_a_key, mint_b_key, AMMError::IdenticalMints);
+    require!(mint_a_key < mint_b_key, AMMError::InvalidMintOrder);
+
+    // Only the fee tiers enabled on the config are allowed
+    require!(
+        ctx.accounts.amm_config.fee_tiers.contains(&fee_bps),
+        AMMError::InvalidFeeTier
+    );
+
+    // The pool starts without liquidity, it comes with the positions and there are no LP tokens
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.token_a_mint = mint_a_key;
+    pool.token_b_mint = mint_b_key;
+    pool.vault_a = ctx.accounts.vault_a.key();
+    pool.vault_b = ctx.accounts.vault_b.key();
+    pool.lp_mint = Pubkey::default();
+    pool.bump = ctx.bumps.liquidity_pool;
+    pool.fee_bps = fee_bps;
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+    pool.status = 0;
+    pool.reserve_a = 0;
+    pool.reserve_b = 0;
+    pool.curve_type = CurveType::Concentrated;
+    pool.initial_amp = 0;
+    pool.target_amp = 0;
+    pool.ramp_start_ts = 0;
+    pool.ramp_stop_ts = 0;
+    pool.weight_a_bps = 0;
+    pool.weight_b_bps = 0;
+    pool.tick_spacing = tick_spacing;
+    pool.sqrt_price = sqrt_price;
+    pool.liquidity = 0;
+    pool.fee_growth_global_a = 0;
+    pool.fee_growth_global_b = 0;
+
+    // Security check for the parameters of the pool's curve
+    pool.validate()?;
+    pool.tick_current = amm_math::tick_at_sqrt_price(sqrt_price).map_err(AMMError::from)?;
+
+    emit!(PoolCreated {
+        pool: pool.key(),
+        creator: ctx.accounts.creator.key(),
+        token_a_mint: pool.token_a_mint,
+        token_b_mint: pool.token_b_mint,
+        fee_bps,
+        amp: 0,
+        weight_a_bps: 0,
+        tick_spacing,
+        sqrt_price,
+        amount_a: 0,
+        amount_b: 0,
+        lp_minted: 0,
+        reserve_a: 0,
+        reserve_b: 0,
+        lp_supply: 0,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::constants::{DEFAULT_FEE_TIERS_BPS, DEFAULT_PROTOCOL_FEE_BPS};
+use crate::errors::AMMError;
+use crate::state::AmmConfig;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AmmConfig::INIT_SPACE,
+        seeds = [b"amm_config"],
+        bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    // Only the upgrade authority of the program can create the config, nobody can front-run the deploy. The
+    // program data is found by its address, the upgradeable loader derives it from the program id
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ AMMError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_initialize_config(ctx: Context<InitializeConfig>, treasury: Pubkey) -> Result<()> {
+    // The upgrade authority creating the singleton config becomes its admin
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.admin = ctx.accounts.admin.key();
+    amm_config.pending_admin = None;
+    amm_config.fee_tiers = DEFAULT_FEE_TIERS_BPS;
+    amm_config.protocol_fee_bps = DEFAULT_PROTOCOL_FEE_BPS;
+    amm_config.treasury = treasury;
+    amm_config.guardian = ctx.accounts.admin.key();
+    amm_config.status = 0;
+    amm_config.bump = ctx.bumps.amm_config;
+
+    emit!(ctx.accounts.amm_config.to_event());
+
+    Ok(())
+}
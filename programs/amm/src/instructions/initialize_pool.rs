@@ -1,13 +1,24 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked}};
 
-use crate::state::Pool;
+use crate::constants::{BPS_DENOMINATOR, MINIMUM_LIQUIDITY};
+use crate::errors::AMMError;
+use crate::events::PoolCreated;
+use crate::math::SafeMath;
+use crate::state::{AmmConfig, CurveType, Pool};
 
 #[derive(Accounts)]
+#[instruction(token_a_amount: u64, token_b_amount: u64, fee_bps: u16, amp: u64, weight_a_bps: u16)]
 pub struct InitializePool<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,
 
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
     pub token_a_mint: InterfaceAccount<'info, Mint>,
 
     pub token_b_mint: InterfaceAccount<'info, Mint>,
@@ -18,7 +29,7 @@ pub struct InitializePool<'info> {
         token::mint = token_a_mint,
         token::authority = liquidity_pool,
         token::token_program = token_program,
-        seeds = [b"vault_a", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_a: InterfaceAccount<'info, TokenAccount>,
@@ -29,7 +40,7 @@ pub struct InitializePool<'info> {
         token::mint = token_b_mint,
         token::authority = liquidity_pool,
         token::token_program = token_program,
-        seeds = [b"vault_b", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_b: InterfaceAccount<'info, TokenAccount>,
@@ -38,7 +49,7 @@ pub struct InitializePool<'info> {
         init,
         payer = creator,
         space = 8 + Pool::INIT_SPACE,
-        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), fee_bps.to_le_bytes().as_ref(), &[CurveType::from_params(amp, weight_a_bps) as u8]],
         bump,
     )]
     pub liquidity_pool: Box<Account<'info, Pool>>,
@@ -55,11 +66,23 @@ pub struct InitializePool<'info> {
         mint::decimals = 6,
         mint::authority = liquidity_pool,
         mint::token_program = token_program,
-        seeds = [b"lp_mint", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"lp_mint", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub lp_mint: InterfaceAccount<'info, Mint>,
 
+    // Owned by itself, nobody can ever sign for it so the LP tokens in it are locked forever
+    #[account(
+        init,
+        payer = creator,
+        token::mint = lp_mint,
+        token::authority = locked_lp_token,
+        token::token_program = token_program,
+        seeds = [b"locked_lp", lp_mint.key().as_ref()],
+        bump,
+    )]
+    pub locked_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
         init_if_needed,
         payer = creator,
@@ -78,7 +101,37 @@ pub fn process_initialize_pool(
     ctx: Context<InitializePool>,
     token_a_amount: u64,
     token_b_amount: u64,
+    fee_bps: u16,
+    amp: u64,
+    weight_a_bps: u16,
 ) -> Result<()> {
+    // Token A has to be the lower pubkey of the two distinct mints, so a pair can't be split across A/B
+    // and B/A pools. It still gets one pool per fee tier and curve
+    let mint_a_key = ctx.accounts.token_a_mint.key();
+    let mint_b_key = ctx.accounts.token_b_mint.key();
+    require_keys_neq!(mint_a_key, mint_b_key, AMMError::IdenticalMints);
+    require!(mint_a_key < mint_b_key, AMMError::InvalidMintOrder);
+
+    // Only the fee tiers enabled on the config are allowed
+    require!(
+        ctx.accounts.amm_config.fee_tiers.contains(&fee_bps),
+        AMMError::InvalidFeeTier
+    );
+
+    // A non-zero amplification coefficient makes a StableSwap pool, its invariant adds up both
+    // reserves so they have to be in the same unit
+    require!(
+        amp == 0 || ctx.accounts.token_a_mint.decimals == ctx.accounts.token_b_mint.decimals,
+        AMMError::MismatchedDecimals
+    );
+
+    // A non-zero weight of token A makes a weighted pool, token B gets the rest of the value
+    let weight_b_bps = match weight_a_bps {
+        0 => 0,
+        _ => (BPS_DENOMINATOR as u16).saturating_sub(weight_a_bps),
+    };
+    let curve_type = CurveType::from_params(amp, weight_a_bps);
+
     let pool = &mut ctx.accounts.liquidity_pool;
     let vault_a = &mut ctx.accounts.vault_a;
     let vault_b = &mut ctx.accounts.vault_b;
@@ -95,6 +148,39 @@ pub fn process_initialize_pool(
     pool.vault_b = vault_b.key();
     pool.lp_mint = ctx.accounts.lp_mint.key();
     pool.bump = ctx.bumps.liquidity_pool;
+    pool.fee_bps = fee_bps;
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+    pool.status = 0;
+    pool.reserve_a = 0;
+    pool.reserve_b = 0;
+    pool.curve_type = curve_type;
+    pool.initial_amp = amp;
+    pool.target_amp = amp;
+    pool.ramp_start_ts = 0;
+    pool.ramp_stop_ts = 0;
+    pool.weight_a_bps = weight_a_bps;
+    pool.weight_b_bps = weight_b_bps;
+    pool.tick_spacing = 0;
+    pool.sqrt_price = 0;
+    pool.tick_current = 0;
+    pool.liquidity = 0;
+    pool.fee_growth_global_a = 0;
+    pool.fee_growth_global_b = 0;
+
+    // Security check for the parameters of the pool's curve
+    pool.validate()?;
+
+    // To Calculate the LP tokens for the initial liquidity
+    // Calculation : sqrt(x * y), D for a stable pool or x^w_a * y^w_b for a weighted pool,
+    // rounded down so the pool is never under-collateralised
+    let lp_token_amount = pool.deposit(token_a_amount, token_b_amount, 0)?.lp_minted;
+
+    // Security check for the first depositor share inflation, part of the supply gets locked
+    require!(lp_token_amount > MINIMUM_LIQUIDITY, AMMError::InsufficientInitialLiquidity);
+
+    pool.reserve_a = token_a_amount;
+    pool.reserve_b = token_b_amount;
 
     // Transfer tokens from creater to the vault
     // Token A : Creator to vault_A
@@ -124,22 +210,45 @@ pub fn process_initialize_pool(
 
     token_interface::transfer_checked(cpi_ctx_token_b, token_b_amount, decimals_token_b)?;
 
-    // To Calculate and Mint LP tokens to the creator 
-    // Calculation : sqrt(x * y)
-    let lp_token_amount = f64::sqrt(token_a_amount as f64 * token_b_amount as f64) as u64;
+    // Mint LP tokens, MINIMUM_LIQUIDITY to the locked account and the rest to the creator
+    let fee_bps_key = fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"pool", mint_a_key.as_ref(), mint_b_key.as_ref(), fee_bps_key.as_ref(), &[curve_type as u8], &[ctx.bumps.liquidity_pool]]];
+
+    let lock_to_accounts = MintTo{
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        to: ctx.accounts.locked_lp_token.to_account_info(),
+        authority: pool.to_account_info()
+    };
+    let cpi_ctx_lock_to = CpiContext::new(token_program.to_account_info(), lock_to_accounts).with_signer(signer_seeds);
+
+    token_interface::mint_to(cpi_ctx_lock_to, MINIMUM_LIQUIDITY)?;
 
-    // Mint 
     let mint_to_accounts = MintTo{
         mint: ctx.accounts.lp_mint.to_account_info(),
         to: ctx.accounts.creator_lp_token.to_account_info(),
         authority: pool.to_account_info()
     };
-    let mint_a_key = mint_a.key();
-    let mint_b_key = mint_b.key();
-    let signer_seeds: &[&[&[u8]]] = &[&[b"pool", mint_a_key.as_ref(), mint_b_key.as_ref(), &[ctx.bumps.liquidity_pool]]];
     let cpi_ctx_mint_to = CpiContext::new(token_program.to_account_info(), mint_to_accounts).with_signer(signer_seeds);
 
-    token_interface::mint_to(cpi_ctx_mint_to, lp_token_amount)?;
+    token_interface::mint_to(cpi_ctx_mint_to, lp_token_amount.safe_sub(MINIMUM_LIQUIDITY)?)?;
+
+    emit!(PoolCreated {
+        pool: pool.key(),
+        creator: ctx.accounts.creator.key(),
+        token_a_mint: pool.token_a_mint,
+        token_b_mint: pool.token_b_mint,
+        fee_bps,
+        amp,
+        weight_a_bps,
+        tick_spacing: 0,
+        sqrt_price: 0,
+        amount_a: token_a_amount,
+        amount_b: token_b_amount,
+        lp_minted: lp_token_amount.safe_sub(MINIMUM_LIQUIDITY)?,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+        lp_supply: lp_token_amount,
+    });
     
     
 
@@ -0,0 +1,49 @@
+use amm_math::{MAX_TICK, MIN_TICK};
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+use crate::state::{Pool, TickArray};
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub liquidity_pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TickArray::INIT_SPACE,
+        seeds = [b"tick_array", liquidity_pool.key().as_ref(), start_tick_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_initialize_tick_array(
+    ctx: Context<InitializeTickArray>,
+    start_tick_index: i32,
+) -> Result<()> {
+    let pool = &ctx.accounts.liquidity_pool;
+    require!(pool.is_concentrated(), AMMError::WrongPoolType);
+
+    // Arrays start at a multiple of the ticks they cover and hold at least one usable tick
+    let ticks_per_array = TickArray::ticks_per_array(pool.tick_spacing);
+    require!(
+        start_tick_index == TickArray::start_tick_index(start_tick_index, pool.tick_spacing)
+            && start_tick_index <= MAX_TICK
+            && start_tick_index + ticks_per_array > MIN_TICK,
+        AMMError::InvalidTickArray
+    );
+
+    // Anyone can pay for an array, every tick starts uninitialized
+    let tick_array = &mut ctx.accounts.tick_array;
+    tick_array.pool = pool.key();
+    tick_array.start_tick_index = start_tick_index;
+
+    Ok(())
+}
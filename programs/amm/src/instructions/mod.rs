@@ -9,3 +9,60 @@ pub use add_liquidity::*;
 
 pub mod remove_liquidity;
 pub use remove_liquidity::*;
+
+pub mod collect_protocol_fees;
+pub use collect_protocol_fees::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod set_admin;
+pub use set_admin::*;
+
+pub mod accept_admin;
+pub use accept_admin::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod set_pool_status;
+pub use set_pool_status::*;
+
+pub mod set_global_status;
+pub use set_global_status::*;
+
+pub mod swap_exact_out;
+pub use swap_exact_out::*;
+
+pub mod sync;
+pub use sync::*;
+
+pub mod skim;
+pub use skim::*;
+
+pub mod quote;
+pub use quote::*;
+
+pub mod ramp_amp;
+pub use ramp_amp::*;
+
+pub mod stop_ramp_amp;
+pub use stop_ramp_amp::*;
+
+pub mod initialize_concentrated_pool;
+pub use initialize_concentrated_pool::*;
+
+pub mod initialize_tick_array;
+pub use initialize_tick_array::*;
+
+pub mod open_position;
+pub use open_position::*;
+
+pub mod increase_liquidity;
+pub use increase_liquidity::*;
+
+pub mod decrease_liquidity;
+pub use decrease_liquidity::*;
+
+pub mod swap_concentrated;
+pub use swap_concentrated::*;
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::concentrated::check_ticks;
+use crate::errors::AMMError;
+use crate::state::{Pool, Position};
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub liquidity_pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [b"position", liquidity_pool.key().as_ref(), owner.key().as_ref(), tick_lower.to_le_bytes().as_ref(), tick_upper.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_open_position(
+    ctx: Context<OpenPosition>,
+    tick_lower: i32,
+    tick_upper: i32,
+) -> Result<()> {
+    let pool = &ctx.accounts.liquidity_pool;
+    require!(pool.is_concentrated(), AMMError::WrongPoolType);
+    check_ticks(tick_lower, tick_upper, pool.tick_spacing)?;
+
+    // The position starts empty, liquidity is added with increase_liquidity
+    let position = &mut ctx.accounts.position;
+    position.pool = pool.key();
+    position.owner = ctx.accounts.owner.key();
+    position.tick_lower = tick_lower;
+    position.tick_upper = tick_upper;
+    position.liquidity = 0;
+    position.fee_growth_inside_last_a = 0;
+    position.fee_growth_inside_last_b = 0;
+    position.tokens_owed_a = 0;
+    position.tokens_owed_b = 0;
+    position.bump = ctx.bumps.position;
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::constants::{STATUS_DEPOSIT_DISABLED, STATUS_SWAP_DISABLED, STATUS_WITHDRAW_DISABLED};
+use crate::errors::AMMError;
+use crate::instructions::add_liquidity::AddLiquidityResult;
+use crate::instructions::remove_liquidity::RemoveLiquidityResult;
+use crate::instructions::swap::SwapResult;
+use crate::state::{AmmConfig, Pool};
+
+// Read-only pool accounts, quotes are meant to be simulated and never move tokens
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [b"lp_mint", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+}
+
+// The spot price only needs the pool, concentrated liquidity pools have no LP mint for Quote
+#[derive(Accounts)]
+pub struct QuoteSpotPrice<'info> {
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Account<'info, Pool>,
+}
+
+// Returned to the caller through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SpotPrice {
+    // Marginal price of token A in token B (Q64.64), in the tokens' smallest units
+    pub price: u128,
+}
+
+pub fn process_quote_swap(ctx: Context<Quote>, amount_in: u64, a_to_b: bool) -> Result<SwapResult> {
+    // A halted swap would fail, so does its quote
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_SWAP_DISABLED),
+        AMMError::OperationHalted
+    );
+
+    let amount_out = ctx
+        .accounts
+        .liquidity_pool
+        .swap_exact_in(amount_in, a_to_b)?;
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+    })
+}
+
+pub fn process_quote_add_liquidity(
+    ctx: Context<Quote>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<AddLiquidityResult> {
+    // A halted deposit would fail, so does its quote
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_DEPOSIT_DISABLED),
+        AMMError::OperationHalted
+    );
+
+    let pool = &ctx.accounts.liquidity_pool;
+    let deposit = pool.deposit(amount_a, amount_b, ctx.accounts.lp_mint.supply)?;
+
+    Ok(deposit.into())
+}
+
+pub fn process_quote_remove_liquidity(
+    ctx: Context<Quote>,
+    lp_amount: u64,
+) -> Result<RemoveLiquidityResult> {
+    // A halted withdrawal would fail, so does its quote
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_WITHDRAW_DISABLED),
+        AMMError::OperationHalted
+    );
+
+    let pool = &ctx.accounts.liquidity_pool;
+    let withdrawal = pool.withdraw(lp_amount, ctx.accounts.lp_mint.supply)?;
+
+    Ok(withdrawal.into())
+}
+
+pub fn process_quote_spot_price(ctx: Context<QuoteSpotPrice>) -> Result<SpotPrice> {
+    let now = Clock::get()?.unix_timestamp;
+    let price = ctx.accounts.liquidity_pool.spot_price(now)?;
+
+    Ok(SpotPrice { price })
+}
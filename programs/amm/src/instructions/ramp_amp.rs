@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION};
+use crate::errors::AMMError;
+use crate::state::{AmmConfig, Pool};
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        has_one = admin @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(mut)]
+    pub liquidity_pool: Account<'info, Pool>,
+}
+
+pub fn process_ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_stop_ts: i64) -> Result<()> {
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Only a stable pool has an amp, a constant product pool can't be turned into one
+    require!(pool.is_stable(), AMMError::NotStablePool);
+    require!(
+        (MIN_AMP..=MAX_AMP).contains(&target_amp),
+        AMMError::InvalidAmp
+    );
+
+    // Security check for the curve moving faster than arbitrage can follow, a ramp replacing the
+    // previous one right away would compound the change
+    require!(
+        now >= pool.ramp_start_ts.saturating_add(MIN_RAMP_DURATION),
+        AMMError::RampTooSoon
+    );
+    require!(
+        ramp_stop_ts >= now.saturating_add(MIN_RAMP_DURATION),
+        AMMError::AmpChangeTooFast
+    );
+
+    let current_amp = pool.current_amp(now);
+    require!(
+        target_amp <= current_amp.saturating_mul(MAX_AMP_CHANGE)
+            && current_amp <= target_amp.saturating_mul(MAX_AMP_CHANGE),
+        AMMError::AmpChangeTooFast
+    );
+
+    // The ramp starts from wherever the previous one got to
+    pool.initial_amp = current_amp;
+    pool.target_amp = target_amp;
+    pool.ramp_start_ts = now;
+    pool.ramp_stop_ts = ramp_stop_ts;
+
+    emit!(pool.to_ramp_event(pool.key(), ctx.accounts.admin.key()));
+
+    Ok(())
+}
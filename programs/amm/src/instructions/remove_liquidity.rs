@@ -4,41 +4,51 @@ use anchor_spl::{
     token_interface::{self, BurnChecked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+use crate::constants::STATUS_WITHDRAW_DISABLED;
 use crate::errors::AMMError;
-use crate::state::Pool;
+use crate::events::LiquidityRemoved;
+use crate::math::SafeMath;
+use crate::state::{AmmConfig, Pool};
+use crate::utils::check_deadline;
 
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
     #[account(mut)]
     pub liquidity_revoker: Signer<'info>,
 
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
     pub token_a_mint: InterfaceAccount<'info, Mint>,
     pub token_b_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
-        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
         bump = liquidity_pool.bump,
     )]
     pub liquidity_pool: Account<'info, Pool>,
 
     #[account(
         mut,
-        seeds = [b"vault_a", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_a: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [b"vault_b", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_b: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [b"lp_mint", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"lp_mint", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub lp_mint: InterfaceAccount<'info, Mint>,
@@ -72,35 +82,57 @@ pub struct RemoveLiquidity<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
+// Returned to the caller through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RemoveLiquidityResult {
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+impl From<amm_math::Withdrawal> for RemoveLiquidityResult {
+    fn from(withdrawal: amm_math::Withdrawal) -> Self {
+        Self {
+            amount_a: withdrawal.amount_a,
+            amount_b: withdrawal.amount_b,
+        }
+    }
+}
+
 pub fn process_remove_liquidity(
     ctx: Context<RemoveLiquidity>,
     lp_amount: u64,
     minimum_token_a: u64,
     minimum_token_b: u64,
-) -> Result<()> {
+    deadline: Option<i64>,
+) -> Result<RemoveLiquidityResult> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the withdrawals being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_WITHDRAW_DISABLED),
+        AMMError::OperationHalted
+    );
+
     // Security Check
     if ctx.accounts.revoker_token_lp.amount == 0 {
         return Err(AMMError::ZeroContriInPool.into());
     }
-    // Store the required states
-    let vault_a_amount = ctx.accounts.vault_a.amount;
-    let vault_b_amount = ctx.accounts.vault_b.amount;
+    // Store the required states (reserves tracked by the pool)
+    let vault_a_amount = ctx.accounts.liquidity_pool.reserve_a;
+    let vault_b_amount = ctx.accounts.liquidity_pool.reserve_b;
     let total_lp_supply = ctx.accounts.lp_mint.supply;
     let token_program = &mut ctx.accounts.token_program;
 
     // Calculate the amount of token_a and token_b w.r.t. lp_tokens revoker holds
-    // required_tokens = lp_tokens alloted / total lp_tokens supply  * total amount in vault of that token
-
-    let required_token_a = (lp_amount as u128)
-        .checked_mul(vault_a_amount as u128)
-        .unwrap()
-        .checked_div(total_lp_supply as u128)
-        .unwrap() as u64;
-    let required_token_b = (lp_amount as u128)
-        .checked_mul(vault_b_amount as u128)
-        .unwrap()
-        .checked_div(total_lp_supply as u128)
-        .unwrap() as u64;
+    let withdrawal = ctx
+        .accounts
+        .liquidity_pool
+        .withdraw(lp_amount, total_lp_supply)?;
+    let required_token_a = withdrawal.amount_a;
+    let required_token_b = withdrawal.amount_b;
 
     // Check for Slippage
     require!(
@@ -121,10 +153,13 @@ pub fn process_remove_liquidity(
     // Transfer the tokens from the vault to the liquidity revoker
     let mint_a_keys = ctx.accounts.token_a_mint.key();
     let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
     let signer_seeds: &[&[&[u8]]] = &[&[
         b"pool",
         mint_a_keys.as_ref(),
         mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[ctx.accounts.liquidity_pool.curve_type as u8],
         &[ctx.accounts.liquidity_pool.bump],
     ]];
 
@@ -159,5 +194,21 @@ pub fn process_remove_liquidity(
 
     token_interface::transfer_checked(cpi_ctx_b, required_token_b, decimals_b)?;
 
-    Ok(())
+    // Update the reserves with the withdrawn amounts
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.reserve_a = vault_a_amount.safe_sub(required_token_a)?;
+    pool.reserve_b = vault_b_amount.safe_sub(required_token_b)?;
+
+    emit!(LiquidityRemoved {
+        pool: pool.key(),
+        user: ctx.accounts.liquidity_revoker.key(),
+        amount_a: required_token_a,
+        amount_b: required_token_b,
+        lp_burned: lp_amount,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+        lp_supply: total_lp_supply.safe_sub(lp_amount)?,
+    });
+
+    Ok(withdrawal.into())
 }
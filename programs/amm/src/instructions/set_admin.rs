@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+use crate::state::AmmConfig;
+
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        has_one = admin @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_set_admin(ctx: Context<SetAdmin>, new_admin: Pubkey) -> Result<()> {
+    // The new admin only takes over once it accepts the role
+    ctx.accounts.amm_config.pending_admin = Some(new_admin);
+
+    emit!(ctx.accounts.amm_config.to_event());
+
+    Ok(())
+}
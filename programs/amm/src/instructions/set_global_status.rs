@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::STATUS_ALL_DISABLED;
+use crate::errors::AMMError;
+use crate::events::StatusChanged;
+use crate::state::AmmConfig;
+
+#[derive(Accounts)]
+pub struct SetGlobalStatus<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        constraint = amm_config.is_guardian_or_admin(&authority.key()) @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_set_global_status(ctx: Context<SetGlobalStatus>, status: u8) -> Result<()> {
+    // Only the known status bits can be set
+    require!(status & !STATUS_ALL_DISABLED == 0, AMMError::InvalidStatus);
+
+    ctx.accounts.amm_config.status = status;
+
+    emit!(StatusChanged {
+        pool: None,
+        authority: ctx.accounts.authority.key(),
+        status,
+    });
+
+    Ok(())
+}
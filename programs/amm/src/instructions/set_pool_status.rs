@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::STATUS_ALL_DISABLED;
+use crate::errors::AMMError;
+use crate::events::StatusChanged;
+use crate::state::{AmmConfig, Pool};
+
+#[derive(Accounts)]
+pub struct SetPoolStatus<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        constraint = amm_config.is_guardian_or_admin(&authority.key()) @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(mut)]
+    pub liquidity_pool: Account<'info, Pool>,
+}
+
+pub fn process_set_pool_status(ctx: Context<SetPoolStatus>, status: u8) -> Result<()> {
+    // Only the known status bits can be set
+    require!(status & !STATUS_ALL_DISABLED == 0, AMMError::InvalidStatus);
+
+    ctx.accounts.liquidity_pool.status = status;
+
+    emit!(StatusChanged {
+        pool: Some(ctx.accounts.liquidity_pool.key()),
+        authority: ctx.accounts.authority.key(),
+        status,
+    });
+
+    Ok(())
+}
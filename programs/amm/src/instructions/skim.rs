@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::events::Skimmed;
+use crate::state::Pool;
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    pub skimmer: Signer<'info>,
+
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::token_program = token_program,
+    )]
+    pub recipient_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_b_mint,
+        token::token_program = token_program,
+    )]
+    pub recipient_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn process_skim(ctx: Context<Skim>) -> Result<()> {
+    // Anything in the vaults on top of the reserves and the protocol fees is unaccounted for
+    let pool = &ctx.accounts.liquidity_pool;
+    let excess_a = ctx
+        .accounts
+        .vault_a
+        .amount
+        .saturating_sub(pool.reserve_a)
+        .saturating_sub(pool.protocol_fees_a);
+    let excess_b = ctx
+        .accounts
+        .vault_b
+        .amount
+        .saturating_sub(pool.reserve_b)
+        .saturating_sub(pool.protocol_fees_b);
+
+    let mint_a_keys = ctx.accounts.token_a_mint.key();
+    let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"pool",
+        mint_a_keys.as_ref(),
+        mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[pool.curve_type as u8],
+        &[pool.bump],
+    ]];
+
+    // Transfer the excess from the vaults to the recipient
+    if excess_a > 0 {
+        let transfer_a_accounts = TransferChecked {
+            from: ctx.accounts.vault_a.to_account_info(),
+            to: ctx.accounts.recipient_token_a.to_account_info(),
+            mint: ctx.accounts.token_a_mint.to_account_info(),
+            authority: pool.to_account_info(),
+        };
+        let cpi_ctx_a = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_a_accounts,
+            signer_seeds,
+        );
+        let decimals_a = ctx.accounts.token_a_mint.decimals;
+        token_interface::transfer_checked(cpi_ctx_a, excess_a, decimals_a)?;
+    }
+
+    if excess_b > 0 {
+        let transfer_b_accounts = TransferChecked {
+            from: ctx.accounts.vault_b.to_account_info(),
+            to: ctx.accounts.recipient_token_b.to_account_info(),
+            mint: ctx.accounts.token_b_mint.to_account_info(),
+            authority: pool.to_account_info(),
+        };
+        let cpi_ctx_b = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_b_accounts,
+            signer_seeds,
+        );
+        let decimals_b = ctx.accounts.token_b_mint.decimals;
+        token_interface::transfer_checked(cpi_ctx_b, excess_b, decimals_b)?;
+    }
+
+    emit!(Skimmed {
+        pool: pool.key(),
+        amount_a: excess_a,
+        amount_b: excess_b,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+use crate::state::{AmmConfig, Pool};
+
+#[derive(Accounts)]
+pub struct StopRampAmp<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        constraint = amm_config.is_guardian_or_admin(&authority.key()) @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(mut)]
+    pub liquidity_pool: Account<'info, Pool>,
+}
+
+pub fn process_stop_ramp_amp(ctx: Context<StopRampAmp>) -> Result<()> {
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(pool.is_stable(), AMMError::NotStablePool);
+
+    // Freeze the amp where the ramp got to, the guardian can stop a ramp but not start one
+    let current_amp = pool.current_amp(now);
+    pool.initial_amp = current_amp;
+    pool.target_amp = current_amp;
+    pool.ramp_start_ts = now;
+    pool.ramp_stop_ts = now;
+
+    emit!(pool.to_ramp_event(pool.key(), ctx.accounts.authority.key()));
+
+    Ok(())
+}
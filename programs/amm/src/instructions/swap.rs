@@ -1,5 +1,9 @@
+use crate::constants::STATUS_SWAP_DISABLED;
 use crate::errors::AMMError;
-use crate::state::Pool;
+use crate::events::Swapped;
+use crate::math::SafeMath;
+use crate::state::{AmmConfig, Pool};
+use crate::utils::check_deadline;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -11,27 +15,33 @@ pub struct Swap<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    #[account(
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
     pub token_a_mint: InterfaceAccount<'info, Mint>,
 
     pub token_b_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
-        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
         bump = liquidity_pool.bump,
     )]
     pub liquidity_pool: Account<'info, Pool>,
 
     #[account(
         mut,
-        seeds = [b"vault_a", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_a: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [b"vault_b", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
         bump,
     )]
     pub vault_b: InterfaceAccount<'info, TokenAccount>,
@@ -58,12 +68,31 @@ pub struct Swap<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
+// Returned to the caller through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapResult {
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
 pub fn process_swap(
     ctx: Context<Swap>,
     amount_in: u64,
     minimum_amount_out: u64,
     a_to_b: bool,
-) -> Result<()> {
+    deadline: Option<i64>,
+) -> Result<SwapResult> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the swaps being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_SWAP_DISABLED),
+        AMMError::OperationHalted
+    );
+
     // Set the correct variables as per the direction of transfer
     let (vault_a, vault_b, user_token_a, user_token_b, mint_a, mint_b) = match a_to_b {
         true => (
@@ -84,9 +113,12 @@ pub fn process_swap(
         ),
     };
 
-    // Store the vault amounts
-    let reserve_in = vault_a.amount;
-    let reserve_out = vault_b.amount;
+    // Store the reserves tracked by the pool
+    let pool = &ctx.accounts.liquidity_pool;
+    let (reserve_in, reserve_out) = match a_to_b {
+        true => (pool.reserve_a, pool.reserve_b),
+        false => (pool.reserve_b, pool.reserve_a),
+    };
 
     // Transfer swap amount to the vault_a
     let transfer_vault_accounts = TransferChecked {
@@ -106,10 +138,19 @@ pub fn process_swap(
     // Since the user swap amount is in the correct vault ,
     // we can now proceed with the calculation of the transfer
 
-    let amount_out = calculate_output_amount(reserve_in, reserve_out, amount_in)?;
+    let amount_out = ctx
+        .accounts
+        .liquidity_pool
+        .swap_exact_in(amount_in, a_to_b)?;
 
     require!(amount_out >= minimum_amount_out, AMMError::SlippageExceeded);
 
+    // Part of the swap fee is set aside for the protocol
+    let swap_fee = amm_math::swap_fee(amount_in, ctx.accounts.liquidity_pool.fee_bps)
+        .map_err(AMMError::from)?;
+    let protocol_fee = amm_math::protocol_fee(swap_fee, ctx.accounts.amm_config.protocol_fee_bps)
+        .map_err(AMMError::from)?;
+
     // The calculated amount will now be transfer to the swapper from vault_b
     let transfer_swapped_accounts = TransferChecked {
         from: vault_b.to_account_info(),
@@ -119,10 +160,13 @@ pub fn process_swap(
     };
     let mint_a_keys = ctx.accounts.token_a_mint.key();
     let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
     let signer_seeds: &[&[&[u8]]] = &[&[
         b"pool",
         mint_a_keys.as_ref(),
         mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[ctx.accounts.liquidity_pool.curve_type as u8],
         &[ctx.accounts.liquidity_pool.bump],
     ]];
     let cpi_ctx_swapped = CpiContext::new(
@@ -134,25 +178,36 @@ pub fn process_swap(
     let decimals = mint_b.decimals;
     token_interface::transfer_checked(cpi_ctx_swapped, amount_out, decimals)?;
 
-    Ok(())
-}
-
-fn calculate_output_amount(vault_a: u64, vault_b: u64, swap_amount: u64) -> Result<u64> {
-    require!(vault_a > 0 && vault_b > 0, AMMError::InsufficientLiquidity);
-    require!(swap_amount > 0, AMMError::InvalidInput);
-
-    // Taking 0.3% fee for liquidity providers
-    let swap_amount_with_fee = (swap_amount as u128)
-        .checked_mul(997)
-        .unwrap()
-        .checked_div(1000)
-        .unwrap();
-
-    // k = x * y
-    let k = (vault_a as u128).checked_mul(vault_b as u128).unwrap();
-    let new_vault_a_amount = (vault_a as u128).checked_add(swap_amount_with_fee).unwrap();
-    let new_vault_b_amount = k.checked_div(new_vault_a_amount).unwrap();
-    let transfer_amount = (vault_b as u128).checked_sub(new_vault_b_amount).unwrap();
-
-    Ok(transfer_amount as u64)
+    // Update the reserves and accrue the protocol fee on the input side of the swap
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let reserve_in_added = amount_in.safe_sub(protocol_fee)?;
+    match a_to_b {
+        true => {
+            pool.reserve_a = reserve_in.safe_add(reserve_in_added)?;
+            pool.reserve_b = reserve_out.safe_sub(amount_out)?;
+            pool.protocol_fees_a = pool.protocol_fees_a.safe_add(protocol_fee)?;
+        }
+        false => {
+            pool.reserve_b = reserve_in.safe_add(reserve_in_added)?;
+            pool.reserve_a = reserve_out.safe_sub(amount_out)?;
+            pool.protocol_fees_b = pool.protocol_fees_b.safe_add(protocol_fee)?;
+        }
+    }
+
+    emit!(Swapped {
+        pool: pool.key(),
+        user: ctx.accounts.signer.key(),
+        a_to_b,
+        amount_in,
+        amount_out,
+        fee: swap_fee,
+        protocol_fee,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+    });
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+    })
 }
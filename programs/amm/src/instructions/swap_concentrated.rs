@@ -0,0 +1,214 @@
+use amm_math::{MAX_SQRT_PRICE, MIN_SQRT_PRICE};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TransferChecked};
+
+use crate::concentrated::TickArrays;
+use crate::constants::STATUS_SWAP_DISABLED;
+use crate::errors::AMMError;
+use crate::events::Swapped;
+use crate::instructions::swap::{Swap, SwapResult};
+use crate::math::SafeMath;
+use crate::utils::check_deadline;
+
+// The tick arrays the swap crosses are passed as remaining accounts, starting with the one holding the
+// current tick and following the direction of the swap
+pub fn process_swap_concentrated<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    a_to_b: bool,
+    deadline: Option<i64>,
+) -> Result<SwapResult> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the swaps being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_SWAP_DISABLED),
+        AMMError::OperationHalted
+    );
+    require!(
+        ctx.accounts.liquidity_pool.is_concentrated(),
+        AMMError::WrongPoolType
+    );
+    require!(amount_in > 0, AMMError::InvalidInput);
+
+    let mut tick_arrays = TickArrays::load(&ctx.accounts.liquidity_pool, ctx.remaining_accounts)?;
+    let protocol_fee_bps = ctx.accounts.amm_config.protocol_fee_bps;
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let sqrt_price_limit = match a_to_b {
+        true => MIN_SQRT_PRICE,
+        false => MAX_SQRT_PRICE,
+    };
+
+    // Swap one range of constant liquidity at a time, crossing the initialized ticks in between
+    let mut amount_remaining = amount_in;
+    let mut amount_out = 0u64;
+    let mut swap_fee = 0u64;
+    let mut protocol_fee = 0u64;
+    while amount_remaining > 0 {
+        require!(
+            pool.sqrt_price != sqrt_price_limit,
+            AMMError::InsufficientLiquidity
+        );
+
+        let (tick_next, initialized) =
+            tick_arrays.next_initialized_tick(pool.tick_current, a_to_b)?;
+        let sqrt_price_target = amm_math::sqrt_price_at_tick(tick_next).map_err(AMMError::from)?;
+        let step = amm_math::compute_swap_step(
+            pool.sqrt_price,
+            sqrt_price_target,
+            pool.liquidity,
+            amount_remaining,
+            pool.fee_bps,
+        )
+        .map_err(AMMError::from)?;
+
+        amount_remaining = amount_remaining
+            .safe_sub(step.amount_in)?
+            .safe_sub(step.fee_amount)?;
+        amount_out = amount_out.safe_add(step.amount_out)?;
+
+        // Part of the fee is set aside for the protocol, the rest grows the fees of the liquidity in range
+        let step_protocol_fee =
+            amm_math::protocol_fee(step.fee_amount, protocol_fee_bps).map_err(AMMError::from)?;
+        swap_fee = swap_fee.safe_add(step.fee_amount)?;
+        protocol_fee = protocol_fee.safe_add(step_protocol_fee)?;
+        if pool.liquidity > 0 {
+            let fee_growth = amm_math::fee_growth_delta(
+                step.fee_amount.safe_sub(step_protocol_fee)?,
+                pool.liquidity,
+            )
+            .map_err(AMMError::from)?;
+            match a_to_b {
+                true => {
+                    pool.fee_growth_global_a = pool.fee_growth_global_a.wrapping_add(fee_growth)
+                }
+                false => {
+                    pool.fee_growth_global_b = pool.fee_growth_global_b.wrapping_add(fee_growth)
+                }
+            }
+        }
+
+        // Crossing a tick moves the liquidity of the positions it bounds in or out of range
+        pool.sqrt_price = step.sqrt_price_next;
+        if step.sqrt_price_next == sqrt_price_target {
+            if initialized {
+                let (fee_growth_global_a, fee_growth_global_b) =
+                    (pool.fee_growth_global_a, pool.fee_growth_global_b);
+                let liquidity_net = tick_arrays
+                    .tick_mut(tick_next)?
+                    .cross(fee_growth_global_a, fee_growth_global_b);
+                let liquidity_delta = match a_to_b {
+                    true => liquidity_net.checked_neg().ok_or(AMMError::MathOverflow)?,
+                    false => liquidity_net,
+                };
+                pool.liquidity = amm_math::add_liquidity_delta(pool.liquidity, liquidity_delta)
+                    .map_err(AMMError::from)?;
+            }
+            pool.tick_current = match a_to_b {
+                true => tick_next - 1,
+                false => tick_next,
+            };
+        } else {
+            pool.tick_current =
+                amm_math::tick_at_sqrt_price(pool.sqrt_price).map_err(AMMError::from)?;
+        }
+    }
+    tick_arrays.save()?;
+
+    require!(amount_out >= minimum_amount_out, AMMError::SlippageExceeded);
+
+    // Set the correct variables as per the direction of transfer
+    let (vault_in, vault_out, user_token_in, user_token_out, mint_in, mint_out) = match a_to_b {
+        true => (
+            &ctx.accounts.vault_a,
+            &ctx.accounts.vault_b,
+            &ctx.accounts.swap_account_a,
+            &ctx.accounts.swap_account_b,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_b_mint,
+        ),
+        false => (
+            &ctx.accounts.vault_b,
+            &ctx.accounts.vault_a,
+            &ctx.accounts.swap_account_b,
+            &ctx.accounts.swap_account_a,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_a_mint,
+        ),
+    };
+
+    // Transfer the swap amount to the input vault
+    let transfer_in_accounts = TransferChecked {
+        from: user_token_in.to_account_info(),
+        to: vault_in.to_account_info(),
+        mint: mint_in.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info(),
+    };
+    let cpi_ctx_in = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_in_accounts,
+    );
+    token_interface::transfer_checked(cpi_ctx_in, amount_in, mint_in.decimals)?;
+
+    // The output is transferred to the swapper from the output vault
+    let transfer_out_accounts = TransferChecked {
+        from: vault_out.to_account_info(),
+        to: user_token_out.to_account_info(),
+        mint: mint_out.to_account_info(),
+        authority: ctx.accounts.liquidity_pool.to_account_info(),
+    };
+    let mint_a_keys = ctx.accounts.token_a_mint.key();
+    let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"pool",
+        mint_a_keys.as_ref(),
+        mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[ctx.accounts.liquidity_pool.curve_type as u8],
+        &[ctx.accounts.liquidity_pool.bump],
+    ]];
+    let cpi_ctx_out = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_out_accounts,
+    )
+    .with_signer(signer_seeds);
+    token_interface::transfer_checked(cpi_ctx_out, amount_out, mint_out.decimals)?;
+
+    // Update the reserves and accrue the protocol fee on the input side of the swap
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let reserve_in_added = amount_in.safe_sub(protocol_fee)?;
+    match a_to_b {
+        true => {
+            pool.reserve_a = pool.reserve_a.safe_add(reserve_in_added)?;
+            pool.reserve_b = pool.reserve_b.safe_sub(amount_out)?;
+            pool.protocol_fees_a = pool.protocol_fees_a.safe_add(protocol_fee)?;
+        }
+        false => {
+            pool.reserve_b = pool.reserve_b.safe_add(reserve_in_added)?;
+            pool.reserve_a = pool.reserve_a.safe_sub(amount_out)?;
+            pool.protocol_fees_b = pool.protocol_fees_b.safe_add(protocol_fee)?;
+        }
+    }
+
+    emit!(Swapped {
+        pool: pool.key(),
+        user: ctx.accounts.signer.key(),
+        a_to_b,
+        amount_in,
+        amount_out,
+        fee: swap_fee,
+        protocol_fee,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+    });
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+    })
+}
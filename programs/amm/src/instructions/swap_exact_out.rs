@@ -0,0 +1,139 @@
+use crate::constants::STATUS_SWAP_DISABLED;
+use crate::errors::AMMError;
+use crate::events::Swapped;
+use crate::instructions::swap::{Swap, SwapResult};
+use crate::math::SafeMath;
+use crate::utils::check_deadline;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TransferChecked};
+
+pub fn process_swap_exact_out(
+    ctx: Context<Swap>,
+    amount_out: u64,
+    maximum_amount_in: u64,
+    a_to_b: bool,
+    deadline: Option<i64>,
+) -> Result<SwapResult> {
+    // Security check for the transaction being executed too late
+    check_deadline(deadline)?;
+
+    // Security check for the swaps being halted
+    require!(
+        !ctx.accounts
+            .amm_config
+            .is_halted(&ctx.accounts.liquidity_pool, STATUS_SWAP_DISABLED),
+        AMMError::OperationHalted
+    );
+
+    // Set the correct variables as per the direction of transfer
+    let (vault_a, vault_b, user_token_a, user_token_b, mint_a, mint_b) = match a_to_b {
+        true => (
+            &ctx.accounts.vault_a,
+            &ctx.accounts.vault_b,
+            &ctx.accounts.swap_account_a,
+            &ctx.accounts.swap_account_b,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_b_mint,
+        ),
+        false => (
+            &ctx.accounts.vault_b,
+            &ctx.accounts.vault_a,
+            &ctx.accounts.swap_account_b,
+            &ctx.accounts.swap_account_a,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_a_mint,
+        ),
+    };
+
+    // Store the reserves tracked by the pool
+    let pool = &ctx.accounts.liquidity_pool;
+    let (reserve_in, reserve_out) = match a_to_b {
+        true => (pool.reserve_a, pool.reserve_b),
+        false => (pool.reserve_b, pool.reserve_a),
+    };
+
+    // Calculate the amount the swapper has to pay for the exact amount out
+    let amount_in = pool.swap_exact_out(amount_out, a_to_b)?;
+
+    require!(amount_in <= maximum_amount_in, AMMError::SlippageExceeded);
+
+    // Part of the swap fee is set aside for the protocol
+    let swap_fee = amm_math::swap_fee(amount_in, pool.fee_bps).map_err(AMMError::from)?;
+    let protocol_fee = amm_math::protocol_fee(swap_fee, ctx.accounts.amm_config.protocol_fee_bps)
+        .map_err(AMMError::from)?;
+
+    // Transfer swap amount to the vault_a
+    let transfer_vault_accounts = TransferChecked {
+        from: user_token_a.to_account_info(),
+        to: vault_a.to_account_info(),
+        mint: mint_a.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info(),
+    };
+    let cpi_ctx_vault_tranfer = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_vault_accounts,
+    );
+
+    let decimals = mint_a.decimals;
+    token_interface::transfer_checked(cpi_ctx_vault_tranfer, amount_in, decimals)?;
+
+    // The exact amount out will now be transfer to the swapper from vault_b
+    let transfer_swapped_accounts = TransferChecked {
+        from: vault_b.to_account_info(),
+        to: user_token_b.to_account_info(),
+        mint: mint_b.to_account_info(),
+        authority: ctx.accounts.liquidity_pool.to_account_info(),
+    };
+    let mint_a_keys = ctx.accounts.token_a_mint.key();
+    let mint_b_keys = ctx.accounts.token_b_mint.key();
+    let fee_bps_keys = ctx.accounts.liquidity_pool.fee_bps.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"pool",
+        mint_a_keys.as_ref(),
+        mint_b_keys.as_ref(),
+        fee_bps_keys.as_ref(),
+        &[ctx.accounts.liquidity_pool.curve_type as u8],
+        &[ctx.accounts.liquidity_pool.bump],
+    ]];
+    let cpi_ctx_swapped = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_swapped_accounts,
+    )
+    .with_signer(signer_seeds);
+
+    let decimals = mint_b.decimals;
+    token_interface::transfer_checked(cpi_ctx_swapped, amount_out, decimals)?;
+
+    // Update the reserves and accrue the protocol fee on the input side of the swap
+    let pool = &mut ctx.accounts.liquidity_pool;
+    let reserve_in_added = amount_in.safe_sub(protocol_fee)?;
+    match a_to_b {
+        true => {
+            pool.reserve_a = reserve_in.safe_add(reserve_in_added)?;
+            pool.reserve_b = reserve_out.safe_sub(amount_out)?;
+            pool.protocol_fees_a = pool.protocol_fees_a.safe_add(protocol_fee)?;
+        }
+        false => {
+            pool.reserve_b = reserve_in.safe_add(reserve_in_added)?;
+            pool.reserve_a = reserve_out.safe_sub(amount_out)?;
+            pool.protocol_fees_b = pool.protocol_fees_b.safe_add(protocol_fee)?;
+        }
+    }
+
+    emit!(Swapped {
+        pool: pool.key(),
+        user: ctx.accounts.signer.key(),
+        a_to_b,
+        amount_in,
+        amount_out,
+        fee: swap_fee,
+        protocol_fee,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+    });
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+    })
+}
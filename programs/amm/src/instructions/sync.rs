@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::events::ReservesSynced;
+use crate::math::SafeMath;
+use crate::state::Pool;
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", token_a_mint.key().as_ref(), token_b_mint.key().as_ref(), liquidity_pool.fee_bps.to_le_bytes().as_ref(), &[liquidity_pool.curve_type as u8]],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [b"vault_a", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"vault_b", liquidity_pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_b: InterfaceAccount<'info, TokenAccount>,
+}
+
+pub fn process_sync(ctx: Context<SyncReserves>) -> Result<()> {
+    // Set the reserves to the vault balances, anything donated to the vaults goes to the liquidity providers
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.reserve_a = ctx.accounts.vault_a.amount.safe_sub(pool.protocol_fees_a)?;
+    pool.reserve_b = ctx.accounts.vault_b.amount.safe_sub(pool.protocol_fees_b)?;
+
+    emit!(ReservesSynced {
+        pool: pool.key(),
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+    });
+
+    Ok(())
+}
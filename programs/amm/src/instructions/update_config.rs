@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::errors::AMMError;
+use crate::state::AmmConfig;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"amm_config"],
+        bump = amm_config.bump,
+        has_one = admin @ AMMError::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_update_config(
+    ctx: Context<UpdateConfig>,
+    fee_tiers: Option<[u16; 4]>,
+    protocol_fee_bps: Option<u16>,
+    treasury: Option<Pubkey>,
+    guardian: Option<Pubkey>,
+) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+
+    // Only the values provided are updated
+    if let Some(fee_tiers) = fee_tiers {
+        // Security check for fee tiers, every pool charges a fee and each tier has to be a different one
+        require!(
+            fee_tiers
+                .iter()
+                .all(|fee| *fee > 0 && (*fee as u64) < BPS_DENOMINATOR),
+            AMMError::InvalidFeeTier
+        );
+        require!(
+            fee_tiers
+                .iter()
+                .enumerate()
+                .all(|(index, fee)| !fee_tiers[..index].contains(fee)),
+            AMMError::InvalidFeeTier
+        );
+        amm_config.fee_tiers = fee_tiers;
+    }
+
+    if let Some(protocol_fee_bps) = protocol_fee_bps {
+        require!(
+            protocol_fee_bps as u64 <= BPS_DENOMINATOR,
+            AMMError::InvalidProtocolFee
+        );
+        amm_config.protocol_fee_bps = protocol_fee_bps;
+    }
+
+    if let Some(treasury) = treasury {
+        amm_config.treasury = treasury;
+    }
+
+    if let Some(guardian) = guardian {
+        amm_config.guardian = guardian;
+    }
+
+    emit!(ctx.accounts.amm_config.to_event());
+
+    Ok(())
+}
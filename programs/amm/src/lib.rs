@@ -3,8 +3,14 @@ use anchor_lang::prelude::*;
 mod instructions;
 use instructions::*;
 
-mod errors;
-mod state;
+mod concentrated;
+pub mod constants;
+mod curve;
+pub mod errors;
+mod events;
+mod math;
+pub mod state;
+mod utils;
 
 declare_id!("WZgFDnddttT4eP5AQMXAYNSqE5v8oHxJPFAWMpTdNzw");
 
@@ -16,8 +22,18 @@ pub mod amm {
         ctx: Context<InitializePool>,
         token_a_amount: u64,
         token_b_amount: u64,
+        fee_bps: u16,
+        amp: u64,
+        weight_a_bps: u16,
     ) -> Result<()> {
-        process_initialize_pool(ctx, token_a_amount, token_b_amount)
+        process_initialize_pool(
+            ctx,
+            token_a_amount,
+            token_b_amount,
+            fee_bps,
+            amp,
+            weight_a_bps,
+        )
     }
 
     pub fn swap(
@@ -25,8 +41,19 @@ pub mod amm {
         amount_in: u64,
         minimum_amount_out: u64,
         a_to_b: bool,
-    ) -> Result<()> {
-        process_swap(ctx, amount_in, minimum_amount_out, a_to_b)
+        deadline: Option<i64>,
+    ) -> Result<SwapResult> {
+        process_swap(ctx, amount_in, minimum_amount_out, a_to_b, deadline)
+    }
+
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+        a_to_b: bool,
+        deadline: Option<i64>,
+    ) -> Result<SwapResult> {
+        process_swap_exact_out(ctx, amount_out, maximum_amount_in, a_to_b, deadline)
     }
 
     pub fn add_liquidity(
@@ -34,8 +61,9 @@ pub mod amm {
         amount_a: u64,
         amount_b: u64,
         minimum_lp_tokens: u64,
-    ) -> Result<()> {
-        process_add_liquidity(ctx, amount_a, amount_b, minimum_lp_tokens)
+        deadline: Option<i64>,
+    ) -> Result<AddLiquidityResult> {
+        process_add_liquidity(ctx, amount_a, amount_b, minimum_lp_tokens, deadline)
     }
 
     pub fn remove_liquidity(
@@ -43,7 +71,135 @@ pub mod amm {
         lp_amount: u64,
         minimum_token_a: u64,
         minimum_token_b: u64,
+        deadline: Option<i64>,
+    ) -> Result<RemoveLiquidityResult> {
+        process_remove_liquidity(ctx, lp_amount, minimum_token_a, minimum_token_b, deadline)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        process_collect_protocol_fees(ctx)
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>, treasury: Pubkey) -> Result<()> {
+        process_initialize_config(ctx, treasury)
+    }
+
+    pub fn set_admin(ctx: Context<SetAdmin>, new_admin: Pubkey) -> Result<()> {
+        process_set_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        process_accept_admin(ctx)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_tiers: Option<[u16; 4]>,
+        protocol_fee_bps: Option<u16>,
+        treasury: Option<Pubkey>,
+        guardian: Option<Pubkey>,
     ) -> Result<()> {
-        process_remove_liquidity(ctx, lp_amount, minimum_token_a, minimum_token_b)
+        process_update_config(ctx, fee_tiers, protocol_fee_bps, treasury, guardian)
+    }
+
+    pub fn set_pool_status(ctx: Context<SetPoolStatus>, status: u8) -> Result<()> {
+        process_set_pool_status(ctx, status)
+    }
+
+    pub fn set_global_status(ctx: Context<SetGlobalStatus>, status: u8) -> Result<()> {
+        process_set_global_status(ctx, status)
+    }
+
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        process_sync(ctx)
+    }
+
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        process_skim(ctx)
+    }
+
+    pub fn quote_swap(ctx: Context<Quote>, amount_in: u64, a_to_b: bool) -> Result<SwapResult> {
+        process_quote_swap(ctx, amount_in, a_to_b)
+    }
+
+    pub fn quote_add_liquidity(
+        ctx: Context<Quote>,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<AddLiquidityResult> {
+        process_quote_add_liquidity(ctx, amount_a, amount_b)
+    }
+
+    pub fn quote_remove_liquidity(
+        ctx: Context<Quote>,
+        lp_amount: u64,
+    ) -> Result<RemoveLiquidityResult> {
+        process_quote_remove_liquidity(ctx, lp_amount)
+    }
+
+    pub fn quote_spot_price(ctx: Context<QuoteSpotPrice>) -> Result<SpotPrice> {
+        process_quote_spot_price(ctx)
+    }
+
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_stop_ts: i64) -> Result<()> {
+        process_ramp_amp(ctx, target_amp, ramp_stop_ts)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<StopRampAmp>) -> Result<()> {
+        process_stop_ramp_amp(ctx)
+    }
+
+    pub fn initialize_concentrated_pool(
+        ctx: Context<InitializeConcentratedPool>,
+        fee_bps: u16,
+        tick_spacing: u16,
+        sqrt_price: u128,
+    ) -> Result<()> {
+        process_initialize_concentrated_pool(ctx, fee_bps, tick_spacing, sqrt_price)
+    }
+
+    pub fn initialize_tick_array(
+        ctx: Context<InitializeTickArray>,
+        start_tick_index: i32,
+    ) -> Result<()> {
+        process_initialize_tick_array(ctx, start_tick_index)
+    }
+
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        process_open_position(ctx, tick_lower, tick_upper)
+    }
+
+    pub fn increase_liquidity(
+        ctx: Context<ModifyLiquidity>,
+        liquidity: u128,
+        maximum_token_a: u64,
+        maximum_token_b: u64,
+        deadline: Option<i64>,
+    ) -> Result<PositionAmounts> {
+        process_increase_liquidity(ctx, liquidity, maximum_token_a, maximum_token_b, deadline)
+    }
+
+    pub fn decrease_liquidity(
+        ctx: Context<ModifyLiquidity>,
+        liquidity: u128,
+        minimum_token_a: u64,
+        minimum_token_b: u64,
+        deadline: Option<i64>,
+    ) -> Result<PositionAmounts> {
+        process_decrease_liquidity(ctx, liquidity, minimum_token_a, minimum_token_b, deadline)
+    }
+
+    pub fn swap_concentrated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        a_to_b: bool,
+        deadline: Option<i64>,
+    ) -> Result<SwapResult> {
+        process_swap_concentrated(ctx, amount_in, minimum_amount_out, a_to_b, deadline)
     }
 }
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+
+// Pricing and LP share math lives in the `amm-math` crate, this only covers the reserve bookkeeping
+
+/// Checked arithmetic that fails with a typed `AMMError` instead of panicking
+pub trait SafeMath: Sized {
+    fn safe_add(self, rhs: Self) -> Result<Self>;
+    fn safe_sub(self, rhs: Self) -> Result<Self>;
+}
+
+impl SafeMath for u64 {
+    fn safe_add(self, rhs: Self) -> Result<Self> {
+        self.checked_add(rhs)
+            .ok_or_else(|| error!(AMMError::MathOverflow))
+    }
+
+    fn safe_sub(self, rhs: Self) -> Result<Self> {
+        self.checked_sub(rhs)
+            .ok_or_else(|| error!(AMMError::MathOverflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_math_maps_failures_to_errors() {
+        assert_eq!(2u64.safe_add(3).unwrap(), 5);
+        assert_eq!(
+            u64::MAX.safe_add(1).unwrap_err(),
+            AMMError::MathOverflow.into()
+        );
+        assert_eq!(0u64.safe_sub(1).unwrap_err(), AMMError::MathOverflow.into());
+    }
+
+    #[test]
+    fn math_errors_map_to_amm_errors() {
+        assert_eq!(
+            Error::from(AMMError::from(amm_math::MathError::Overflow)),
+            AMMError::MathOverflow.into()
+        );
+        assert_eq!(
+            Error::from(AMMError::from(amm_math::MathError::EmptyPool)),
+            AMMError::EmptyPool.into()
+        );
+    }
+}
@@ -1,5 +1,11 @@
+use amm_math::{Deposit, Withdrawal};
 use anchor_lang::prelude::*;
 
+use crate::constants::TICK_ARRAY_SIZE;
+use crate::curve::SwapCurve;
+use crate::errors::AMMError;
+use crate::events::{AmpRamped, ConfigUpdated};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Pool {
@@ -9,4 +15,440 @@ pub struct Pool {
     pub vault_b: Pubkey,
     pub lp_mint: Pubkey,
     pub bump: u8,
+    // Swap fee charged by this pool, in basis points
+    pub fee_bps: u16,
+    // Protocol fees accrued in the vaults but not yet collected by the treasury
+    pub protocol_fees_a: u64,
+    pub protocol_fees_b: u64,
+    // Bitmask of the halted operations on this pool
+    pub status: u8,
+    // Reserves tracked by the pool, tokens donated to the vaults are not part of them
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    // Pricing curve of the pool, the fields below hold the parameters of each curve and are 0 for the others
+    pub curve_type: CurveType,
+    // Amplification coefficient of a StableSwap pool ramping linearly from initial_amp at
+    // ramp_start_ts to target_amp at ramp_stop_ts, both 0 for a constant product pool
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_ts: i64,
+    pub ramp_stop_ts: i64,
+    // Share of the pool's value held in each token (in basis points) of a weighted pool, both 0 otherwise
+    pub weight_a_bps: u16,
+    pub weight_b_bps: u16,
+    // Spacing between the usable ticks of a concentrated liquidity pool, 0 for the other pools
+    pub tick_spacing: u16,
+    // Square root price (Q64.64) of a concentrated liquidity pool, the tick it falls in and the liquidity
+    // of the positions in range
+    pub sqrt_price: u128,
+    pub tick_current: i32,
+    pub liquidity: u128,
+    // Swap fees earned per unit of liquidity (Q64.64) since the concentrated liquidity pool was created
+    pub fee_growth_global_a: u128,
+    pub fee_growth_global_b: u128,
+}
+
+impl Pool {
+    pub fn is_stable(&self) -> bool {
+        self.curve_type == CurveType::StableSwap
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.curve_type == CurveType::Weighted
+    }
+
+    pub fn is_concentrated(&self) -> bool {
+        self.curve_type == CurveType::Concentrated
+    }
+
+    // Amplification coefficient at the unix timestamp `now`
+    pub fn current_amp(&self, now: i64) -> u64 {
+        if now >= self.ramp_stop_ts {
+            return self.target_amp;
+        }
+        if now <= self.ramp_start_ts {
+            return self.initial_amp;
+        }
+
+        // Both timestamps are ordered and amps are bounded by MAX_AMP, none of this can overflow
+        let elapsed = (now - self.ramp_start_ts) as u128;
+        let duration = (self.ramp_stop_ts - self.ramp_start_ts) as u128;
+        let (initial, target) = (self.initial_amp as u128, self.target_amp as u128);
+        let amp = match target > initial {
+            true => initial + (target - initial) * elapsed / duration,
+            false => initial - (initial - target) * elapsed / duration,
+        };
+        amp as u64
+    }
+
+    pub fn to_ramp_event(&self, pool: Pubkey, authority: Pubkey) -> AmpRamped {
+        AmpRamped {
+            pool,
+            authority,
+            initial_amp: self.initial_amp,
+            target_amp: self.target_amp,
+            ramp_start_ts: self.ramp_start_ts,
+            ramp_stop_ts: self.ramp_stop_ts,
+        }
+    }
+
+    // Pricing follows the curve the pool was initialized with, see curve.rs
+    pub fn validate(&self) -> Result<()> {
+        self.curve_type.validate(self)
+    }
+
+    pub fn swap_exact_in(&self, amount_in: u64, a_to_b: bool) -> Result<u64> {
+        self.curve_type.swap_exact_in(self, amount_in, a_to_b)
+    }
+
+    pub fn swap_exact_out(&self, amount_out: u64, a_to_b: bool) -> Result<u64> {
+        self.curve_type.swap_exact_out(self, amount_out, a_to_b)
+    }
+
+    pub fn deposit(&self, amount_a: u64, amount_b: u64, lp_supply: u64) -> Result<Deposit> {
+        self.curve_type
+            .deposit_shares(self, amount_a, amount_b, lp_supply)
+    }
+
+    pub fn withdraw(&self, lp_amount: u64, lp_supply: u64) -> Result<Withdrawal> {
+        self.curve_type.withdraw_amounts(self, lp_amount, lp_supply)
+    }
+
+    // Shared with off-chain readers, which pass their own idea of the current time
+    pub fn spot_price(&self, now: i64) -> Result<u128> {
+        self.curve_type.spot_price(self, now)
+    }
+}
+
+// Pricing curve of a pool, picked when it's created. Its index is one of the pool seeds, so every
+// curve gets its own pool per pair and fee tier and the variants must never be reordered
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum CurveType {
+    // x * y = k
+    ConstantProduct,
+    // StableSwap invariant with an amplification coefficient
+    StableSwap,
+    // Weighted product invariant
+    Weighted,
+    // Liquidity concentrated between the ticks of positions
+    Concentrated,
+}
+
+impl CurveType {
+    // Curve of a pool created by `initialize_pool`, a non-zero amp makes a StableSwap pool and a
+    // non-zero weight of token A a weighted one
+    pub fn from_params(amp: u64, weight_a_bps: u16) -> Self {
+        if amp != 0 {
+            CurveType::StableSwap
+        } else if weight_a_bps != 0 {
+            CurveType::Weighted
+        } else {
+            CurveType::ConstantProduct
+        }
+    }
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct Tick {
+    pub initialized: bool,
+    // Liquidity added to the pool when the price crosses the tick going up, removed going down
+    pub liquidity_net: i128,
+    // Liquidity of every position bounded by the tick, it can be cleared once nothing uses it
+    pub liquidity_gross: u128,
+    // Fee growth on the other side of the tick from the current price, flipped every time it's crossed
+    pub fee_growth_outside_a: u128,
+    pub fee_growth_outside_b: u128,
+}
+
+impl Tick {
+    // Adds a position's liquidity to the tick, bounding the position from below or from above. A tick left
+    // without liquidity keeps its fee growth until the position's fees are accrued, then gets cleared
+    pub fn update(
+        &mut self,
+        tick_index: i32,
+        pool: &Pool,
+        liquidity_delta: i128,
+        upper: bool,
+    ) -> Result<()> {
+        // All the fee growth before the tick is used is assumed to have happened below it
+        if self.liquidity_gross == 0 && tick_index <= pool.tick_current {
+            self.fee_growth_outside_a = pool.fee_growth_global_a;
+            self.fee_growth_outside_b = pool.fee_growth_global_b;
+        }
+
+        self.liquidity_gross = amm_math::add_liquidity_delta(self.liquidity_gross, liquidity_delta)
+            .map_err(AMMError::from)?;
+        self.liquidity_net = match upper {
+            true => self.liquidity_net.checked_sub(liquidity_delta),
+            false => self.liquidity_net.checked_add(liquidity_delta),
+        }
+        .ok_or(AMMError::MathOverflow)?;
+        self.initialized = true;
+
+        Ok(())
+    }
+
+    // Flips the fee growth outside as the price crosses the tick, returns the liquidity to add going up
+    pub fn cross(&mut self, fee_growth_global_a: u128, fee_growth_global_b: u128) -> i128 {
+        self.fee_growth_outside_a = fee_growth_global_a.wrapping_sub(self.fee_growth_outside_a);
+        self.fee_growth_outside_b = fee_growth_global_b.wrapping_sub(self.fee_growth_outside_b);
+        self.liquidity_net
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TickArray {
+    pub pool: Pubkey,
+    // First tick of the array, a multiple of the ticks it covers
+    pub start_tick_index: i32,
+    pub ticks: [Tick; TICK_ARRAY_SIZE],
+}
+
+impl TickArray {
+    // Number of ticks between the start of two consecutive arrays
+    pub fn ticks_per_array(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE as i32 * tick_spacing as i32
+    }
+
+    // Start of the array holding `tick_index`
+    pub fn start_tick_index(tick_index: i32, tick_spacing: u16) -> i32 {
+        let ticks_per_array = Self::ticks_per_array(tick_spacing);
+        tick_index.div_euclid(ticks_per_array) * ticks_per_array
+    }
+
+    // Position of a usable tick in the array, None if the array doesn't hold it
+    pub fn offset(&self, tick_index: i32, tick_spacing: u16) -> Option<usize> {
+        let spacing = tick_spacing as i32;
+        let distance = tick_index - self.start_tick_index;
+        match tick_index % spacing == 0
+            && (0..Self::ticks_per_array(tick_spacing)).contains(&distance)
+        {
+            true => Some((distance / spacing) as usize),
+            false => None,
+        }
+    }
+
+    // Next initialized tick a swap reaches from `tick_current` without leaving the array, the last tick of
+    // the array otherwise. Going down the current tick is included, None if the search starts in another array
+    pub fn next_initialized_tick(
+        &self,
+        tick_current: i32,
+        tick_spacing: u16,
+        a_to_b: bool,
+    ) -> Option<(i32, bool)> {
+        let spacing = tick_spacing as i32;
+        let start = match a_to_b {
+            true => tick_current.div_euclid(spacing) * spacing,
+            false => (tick_current.div_euclid(spacing) + 1) * spacing,
+        };
+        let start_offset = self.offset(start, tick_spacing)?;
+
+        let mut offset = start_offset;
+        while !self.ticks[offset].initialized {
+            match a_to_b {
+                true if offset > 0 => offset -= 1,
+                false if offset < TICK_ARRAY_SIZE - 1 => offset += 1,
+                _ => break,
+            }
+        }
+        let tick_index = self.start_tick_index + offset as i32 * spacing;
+        Some((tick_index, self.ticks[offset].initialized))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    // Liquidity of the position, provided between the prices of its two ticks
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    // Fee growth inside the range when the fees owed were last updated
+    pub fee_growth_inside_last_a: u128,
+    pub fee_growth_inside_last_b: u128,
+    // Fees earned by the position, paid out on its next withdrawal
+    pub tokens_owed_a: u64,
+    pub tokens_owed_b: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AmmConfig {
+    pub admin: Pubkey,
+    // Admin proposed by the current admin, has to accept before taking over
+    pub pending_admin: Option<Pubkey>,
+    // Swap fee tiers (in basis points) pools can be created with
+    pub fee_tiers: [u16; 4],
+    // Share of the swap fee owed to the protocol, in basis points of the fee
+    pub protocol_fee_bps: u16,
+    // Receiver of the protocol share of the swap fees
+    pub treasury: Pubkey,
+    // Authority allowed to halt operations next to the admin
+    pub guardian: Pubkey,
+    // Bitmask of the operations halted on every pool
+    pub status: u8,
+    pub bump: u8,
+}
+
+impl AmmConfig {
+    pub fn to_event(&self) -> ConfigUpdated {
+        ConfigUpdated {
+            admin: self.admin,
+            pending_admin: self.pending_admin,
+            fee_tiers: self.fee_tiers,
+            protocol_fee_bps: self.protocol_fee_bps,
+            treasury: self.treasury,
+            guardian: self.guardian,
+        }
+    }
+
+    // The operation is halted if either the config or the pool has its bit set
+    pub fn is_halted(&self, pool: &Pool, status_flag: u8) -> bool {
+        (self.status | pool.status) & status_flag != 0
+    }
+
+    pub fn is_guardian_or_admin(&self, authority: &Pubkey) -> bool {
+        *authority == self.guardian || *authority == self.admin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramping_pool(initial_amp: u64, target_amp: u64) -> Pool {
+        Pool {
+            token_a_mint: Pubkey::default(),
+            token_b_mint: Pubkey::default(),
+            vault_a: Pubkey::default(),
+            vault_b: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            bump: 0,
+            fee_bps: 1,
+            protocol_fees_a: 0,
+            protocol_fees_b: 0,
+            status: 0,
+            reserve_a: 0,
+            reserve_b: 0,
+            curve_type: CurveType::StableSwap,
+            initial_amp,
+            target_amp,
+            ramp_start_ts: 1_000,
+            ramp_stop_ts: 2_000,
+            weight_a_bps: 0,
+            weight_b_bps: 0,
+            tick_spacing: 0,
+            sqrt_price: 0,
+            tick_current: 0,
+            liquidity: 0,
+            fee_growth_global_a: 0,
+            fee_growth_global_b: 0,
+        }
+    }
+
+    #[test]
+    fn amp_ramps_linearly_between_the_timestamps() {
+        let up = ramping_pool(100, 1_000);
+        assert_eq!(up.current_amp(0), 100);
+        assert_eq!(up.current_amp(1_000), 100);
+        assert_eq!(up.current_amp(1_500), 550);
+        assert_eq!(up.current_amp(2_000), 1_000);
+        assert_eq!(up.current_amp(i64::MAX), 1_000);
+
+        let down = ramping_pool(1_000, 100);
+        assert_eq!(down.current_amp(1_250), 775);
+        assert_eq!(down.current_amp(1_999), 101);
+        assert_eq!(down.current_amp(2_000), 100);
+    }
+
+    fn tick_array(start_tick_index: i32, initialized: &[usize]) -> TickArray {
+        let mut ticks = [Tick::default(); TICK_ARRAY_SIZE];
+        for offset in initialized {
+            ticks[*offset].initialized = true;
+        }
+        TickArray {
+            pool: Pubkey::default(),
+            start_tick_index,
+            ticks,
+        }
+    }
+
+    #[test]
+    fn tick_arrays_start_at_multiples_of_their_ticks() {
+        assert_eq!(TickArray::start_tick_index(0, 64), 0);
+        assert_eq!(TickArray::start_tick_index(2_047, 64), 0);
+        assert_eq!(TickArray::start_tick_index(2_048, 64), 2_048);
+        assert_eq!(TickArray::start_tick_index(-1, 64), -2_048);
+
+        let array = tick_array(-320, &[]);
+        assert_eq!(array.offset(-320, 10), Some(0));
+        assert_eq!(array.offset(-10, 10), Some(31));
+        assert_eq!(array.offset(-15, 10), None);
+        assert_eq!(array.offset(0, 10), None);
+    }
+
+    #[test]
+    fn next_initialized_tick_stays_in_the_array() {
+        let array = tick_array(0, &[5, 20]);
+
+        // Going down the current tick is included, going up it's not
+        assert_eq!(array.next_initialized_tick(120, 10, true), Some((50, true)));
+        assert_eq!(array.next_initialized_tick(50, 10, true), Some((50, true)));
+        assert_eq!(array.next_initialized_tick(49, 10, true), Some((0, false)));
+        assert_eq!(
+            array.next_initialized_tick(50, 10, false),
+            Some((200, true))
+        );
+        assert_eq!(
+            array.next_initialized_tick(200, 10, false),
+            Some((310, false))
+        );
+        assert_eq!(array.next_initialized_tick(-1, 10, false), Some((50, true)));
+
+        // The search starts in the neighbouring array
+        assert_eq!(array.next_initialized_tick(-1, 10, true), None);
+        assert_eq!(array.next_initialized_tick(310, 10, false), None);
+    }
+
+    #[test]
+    fn ticks_track_the_fee_growth_outside() {
+        let mut pool = ramping_pool(0, 0);
+        pool.tick_current = 10;
+        pool.fee_growth_global_a = 500;
+        pool.fee_growth_global_b = 700;
+
+        // Fees so far are assumed below a new tick under the current one, none below one above it
+        let mut below = Tick::default();
+        below.update(0, &pool, 1_000, false).unwrap();
+        assert_eq!(
+            (below.fee_growth_outside_a, below.fee_growth_outside_b),
+            (500, 700)
+        );
+        assert_eq!((below.liquidity_gross, below.liquidity_net), (1_000, 1_000));
+
+        let mut above = Tick::default();
+        above.update(20, &pool, 1_000, true).unwrap();
+        assert_eq!(
+            (above.fee_growth_outside_a, above.fee_growth_outside_b),
+            (0, 0)
+        );
+        assert_eq!(
+            (above.liquidity_gross, above.liquidity_net),
+            (1_000, -1_000)
+        );
+        assert!(below.initialized && above.initialized);
+
+        // Crossing flips the growth to the other side
+        assert_eq!(above.cross(800, 900), -1_000);
+        assert_eq!(
+            (above.fee_growth_outside_a, above.fee_growth_outside_b),
+            (800, 900)
+        );
+    }
 }
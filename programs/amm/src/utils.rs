@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AMMError;
+
+// Rejects the instruction once the unix timestamp deadline provided by the user has passed
+pub fn check_deadline(deadline: Option<i64>) -> Result<()> {
+    if let Some(deadline) = deadline {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, AMMError::Expired);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,1228 @@
+// Runs the program as a native builtin inside solana-program-test, next to the real SPL token & ATA
+// programs, so a plain `cargo test` covers the whole pool lifecycle without a validator or an SBF build.
+// Anchor's CPIs only reach the program-test syscall stubs through the solana-invoke patch in the
+// workspace manifest
+
+use amm::constants::{STATUS_ALL_DISABLED, STATUS_SWAP_DISABLED};
+use amm::errors::AMMError;
+use amm::state::CurveType;
+use amm_client::{instructions, PoolKeys, PositionKeys};
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{
+    self, get_program_data_address, UpgradeableLoaderState,
+};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account;
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+const FEE_BPS: u16 = 30;
+const INITIAL_BALANCE: u64 = 1_000_000_000_000;
+const DAY: i64 = 86_400;
+const TICK_SPACING: u16 = 10;
+
+// Anchor's entrypoint ties the accounts slice to the accounts' lifetime, processor! can't express that
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    amm::entry(program_id, accounts, data)
+}
+
+struct Env {
+    ctx: ProgramTestContext,
+    keys: PoolKeys,
+}
+
+impl Env {
+    async fn new() -> Self {
+        Self::with_decimals(6, 9).await
+    }
+
+    // Two fresh mints, the payer holding INITIAL_BALANCE of both and the config initialized
+    async fn with_decimals(decimals_x: u8, decimals_y: u8) -> Self {
+        // The program runs as a native builtin, the program data only has to name its upgrade authority,
+        // the only one allowed to create the config
+        let upgrade_authority = Keypair::new();
+        let program_data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(upgrade_authority.pubkey()),
+        })
+        .unwrap();
+
+        let mut program_test = ProgramTest::new("amm", amm::ID, processor!(process_instruction));
+        program_test.prefer_bpf(false);
+        program_test.add_account(
+            get_program_data_address(&amm::ID),
+            Account {
+                lamports: Rent::default().minimum_balance(program_data.len()),
+                data: program_data,
+                owner: bpf_loader_upgradeable::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        program_test.add_account(
+            upgrade_authority.pubkey(),
+            Account::new(INITIAL_BALANCE, 0, &solana_sdk::system_program::ID),
+        );
+        let mut ctx = program_test.start_with_context().await;
+
+        let payer = ctx.payer.pubkey();
+        let mint_x = create_mint(&mut ctx, decimals_x).await;
+        let mint_y = create_mint(&mut ctx, decimals_y).await;
+        let keys = PoolKeys::new(
+            &mint_x,
+            &mint_y,
+            FEE_BPS,
+            CurveType::ConstantProduct,
+            &spl_token::ID,
+        );
+
+        let mut env = Self { ctx, keys };
+        for mint in [mint_x, mint_y] {
+            env.fund(&payer, &mint, INITIAL_BALANCE).await;
+        }
+
+        // The upgrade authority creates the config and hands the admin role over to the payer
+        let authority = upgrade_authority.pubkey();
+        let config_instructions = [
+            instructions::initialize_config(&authority, &payer),
+            instructions::set_admin(&authority, &payer),
+            instructions::accept_admin(&payer),
+        ];
+        env.process_signed(&config_instructions, &upgrade_authority)
+            .await
+            .unwrap();
+
+        env
+    }
+
+    fn payer(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    async fn process(&mut self, instructions: &[Instruction]) -> Result<(), BanksClientError> {
+        let blockhash = self.ctx.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.ctx.payer.pubkey()),
+            &[&self.ctx.payer],
+            blockhash,
+        );
+        self.ctx.banks_client.process_transaction(transaction).await
+    }
+
+    // Same as `process`, with `signer` co-signing next to the payer
+    async fn process_signed(
+        &mut self,
+        instructions: &[Instruction],
+        signer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.ctx.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.ctx.payer.pubkey()),
+            &[&self.ctx.payer, signer],
+            blockhash,
+        );
+        self.ctx.banks_client.process_transaction(transaction).await
+    }
+
+    // Creates the owner's ATA for `mint` and mints `amount` into it, the payer is the mint authority
+    async fn fund(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        let ata = self.keys.associated_token_address(owner, mint);
+        let instructions = [
+            create_associated_token_account(&payer, owner, mint, &spl_token::ID),
+            spl_token::instruction::mint_to(&spl_token::ID, mint, &ata, &payer, &[], amount)
+                .unwrap(),
+        ];
+        self.process(&instructions).await.unwrap();
+    }
+
+    // Sends `amount` of the payer's `mint` straight to a vault, without the pool accounting for it
+    async fn donate(&mut self, vault: &Pubkey, mint: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        let source = self.keys.associated_token_address(&payer, mint);
+        let transfer =
+            spl_token::instruction::transfer(&spl_token::ID, &source, vault, &payer, &[], amount)
+                .unwrap();
+        self.process(&[transfer]).await.unwrap();
+    }
+
+    async fn balance(&mut self, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        let ata = self.keys.associated_token_address(owner, mint);
+        self.token_account_amount(&ata).await
+    }
+
+    async fn token_account_amount(&mut self, address: &Pubkey) -> u64 {
+        let account = self.ctx.banks_client.get_account(*address).await.unwrap();
+        spl_token::state::Account::unpack(&account.unwrap().data)
+            .unwrap()
+            .amount
+    }
+
+    async fn lp_supply(&mut self) -> u64 {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(self.keys.lp_mint)
+            .await
+            .unwrap();
+        spl_token::state::Mint::unpack(&account.unwrap().data)
+            .unwrap()
+            .supply
+    }
+
+    async fn pool(&mut self) -> amm::state::Pool {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(self.keys.pool)
+            .await
+            .unwrap();
+        amm_client::decode_pool(&account.unwrap().data).unwrap()
+    }
+
+    async fn config(&mut self) -> amm::state::AmmConfig {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(self.keys.config)
+            .await
+            .unwrap();
+        amm_client::decode_config(&account.unwrap().data).unwrap()
+    }
+
+    async fn now(&mut self) -> i64 {
+        let clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp
+    }
+
+    // Moves the cluster's unix timestamp, the slot stays where it is
+    async fn warp_to_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    async fn create_pool(&mut self, amount_a: u64, amount_b: u64) {
+        self.try_create_pool(amount_a, amount_b, 0, 0)
+            .await
+            .unwrap();
+    }
+
+    async fn try_create_pool(
+        &mut self,
+        amount_a: u64,
+        amount_b: u64,
+        amp: u64,
+        weight_a_bps: u16,
+    ) -> Result<(), BanksClientError> {
+        // The pool's address depends on the curve the amp and weight pick
+        let keys = &self.keys;
+        self.keys = PoolKeys::new(
+            &keys.token_a_mint,
+            &keys.token_b_mint,
+            keys.fee_bps,
+            CurveType::from_params(amp, weight_a_bps),
+            &keys.token_program,
+        );
+        let payer = self.payer();
+        let keys = self.keys;
+        self.process(&[instructions::initialize_pool(
+            &payer,
+            &keys,
+            amount_a,
+            amount_b,
+            amp,
+            weight_a_bps,
+        )])
+        .await
+    }
+}
+
+// Concentrated liquidity pool at a price of 1 with 10 ticks between usable ones, and the payer's
+// positions between ticks -100 and 100 then -200 and -100, each with the same liquidity
+async fn create_concentrated_pool(env: &mut Env, liquidity: u128) -> [PositionKeys; 2] {
+    let keys = &env.keys;
+    env.keys = PoolKeys::new(
+        &keys.token_a_mint,
+        &keys.token_b_mint,
+        keys.fee_bps,
+        CurveType::Concentrated,
+        &keys.token_program,
+    );
+    let (payer, keys) = (env.payer(), env.keys);
+    let positions = [(-100, 100), (-200, -100)]
+        .map(|(lower, upper)| PositionKeys::new(&keys, &payer, TICK_SPACING, lower, upper));
+
+    env.process(&[
+        instructions::initialize_concentrated_pool(&payer, &keys, TICK_SPACING, 1 << 64),
+        instructions::initialize_tick_array(&payer, &keys, -320),
+        instructions::initialize_tick_array(&payer, &keys, 0),
+    ])
+    .await
+    .unwrap();
+    for position in &positions {
+        env.process(&[
+            instructions::open_position(&payer, &keys, position),
+            instructions::increase_liquidity(
+                &payer,
+                &keys,
+                position,
+                liquidity,
+                u64::MAX,
+                u64::MAX,
+                None,
+            ),
+        ])
+        .await
+        .unwrap();
+    }
+
+    positions
+}
+
+async fn create_mint(ctx: &mut ProgramTestContext, decimals: u8) -> Pubkey {
+    let mint = Keypair::new();
+    let payer = ctx.payer.pubkey();
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &payer,
+            None,
+            decimals,
+        )
+        .unwrap(),
+    ];
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&ctx.payer, &mint],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    mint.pubkey()
+}
+
+fn custom_error(result: Result<(), BanksClientError>) -> u32 {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn update_config_rejects_a_zero_fee_tier() {
+    let mut env = Env::new().await;
+    let payer = env.payer();
+
+    let fee_tiers = Some([0, 5, 30, 100]);
+    let result = env
+        .process(&[instructions::update_config(
+            &payer, fee_tiers, None, None, None,
+        )])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::InvalidFeeTier));
+}
+
+#[tokio::test]
+async fn update_config_rejects_duplicate_fee_tiers() {
+    let mut env = Env::new().await;
+    let payer = env.payer();
+
+    let fee_tiers = Some([5, 30, 30, 100]);
+    let result = env
+        .process(&[instructions::update_config(
+            &payer, fee_tiers, None, None, None,
+        )])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::InvalidFeeTier));
+
+    // Four different non-zero tiers go through
+    let fee_tiers = Some([5, 30, 100, 500]);
+    env.process(&[instructions::update_config(
+        &payer, fee_tiers, None, None, None,
+    )])
+    .await
+    .unwrap();
+    assert_eq!(env.config().await.fee_tiers, [5, 30, 100, 500]);
+}
+
+#[tokio::test]
+async fn initialize_pool_locks_minimum_liquidity() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+
+    env.create_pool(4_000_000, 1_000_000).await;
+
+    // sqrt(4e6 * 1e6) = 2e6 LP tokens, 1_000 of them locked forever
+    assert_eq!(env.lp_supply().await, 2_000_000);
+    assert_eq!(env.balance(&payer, &keys.lp_mint).await, 1_999_000);
+    assert_eq!(env.token_account_amount(&keys.vault_a).await, 4_000_000);
+    assert_eq!(env.token_account_amount(&keys.vault_b).await, 1_000_000);
+    assert_eq!(
+        env.balance(&payer, &keys.token_a_mint).await,
+        INITIAL_BALANCE - 4_000_000
+    );
+
+    let pool = env.pool().await;
+    assert_eq!((pool.reserve_a, pool.reserve_b), (4_000_000, 1_000_000));
+    assert_eq!(pool.fee_bps, FEE_BPS);
+}
+
+#[tokio::test]
+async fn swap_pays_out_the_quoted_amount() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let expected_out = amm_math::swap_exact_in(10_000_000, 20_000_000, 100_000, FEE_BPS).unwrap();
+    let balance_a = env.balance(&payer, &keys.token_a_mint).await;
+    let balance_b = env.balance(&payer, &keys.token_b_mint).await;
+
+    env.process(&[instructions::swap(
+        &payer,
+        &keys,
+        100_000,
+        expected_out,
+        true,
+        None,
+    )])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        env.balance(&payer, &keys.token_a_mint).await,
+        balance_a - 100_000
+    );
+    assert_eq!(
+        env.balance(&payer, &keys.token_b_mint).await,
+        balance_b + expected_out
+    );
+    assert_eq!(
+        env.token_account_amount(&keys.vault_b).await,
+        20_000_000 - expected_out
+    );
+
+    // Part of the fee is set aside for the protocol, the rest stays in the reserves
+    let pool = env.pool().await;
+    let protocol_fee =
+        amm_math::protocol_fee(amm_math::swap_fee(100_000, FEE_BPS).unwrap(), 1_667).unwrap();
+    assert_eq!(pool.protocol_fees_a, protocol_fee);
+    assert_eq!(pool.reserve_a, 10_000_000 + 100_000 - protocol_fee);
+    assert_eq!(pool.reserve_b, 20_000_000 - expected_out);
+}
+
+#[tokio::test]
+async fn swap_rejects_slippage() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let expected_out = amm_math::swap_exact_in(20_000_000, 10_000_000, 50_000, FEE_BPS).unwrap();
+    let result = env
+        .process(&[instructions::swap(
+            &payer,
+            &keys,
+            50_000,
+            expected_out + 1,
+            false,
+            None,
+        )])
+        .await;
+
+    assert_eq!(custom_error(result), u32::from(AMMError::SlippageExceeded));
+    assert_eq!(env.pool().await.reserve_b, 20_000_000);
+}
+
+#[tokio::test]
+async fn swap_exact_out_charges_the_quoted_amount() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let expected_in = amm_math::swap_exact_out(10_000_000, 20_000_000, 100_000, FEE_BPS).unwrap();
+    let balance_a = env.balance(&payer, &keys.token_a_mint).await;
+    let balance_b = env.balance(&payer, &keys.token_b_mint).await;
+
+    env.process(&[instructions::swap_exact_out(
+        &payer,
+        &keys,
+        100_000,
+        expected_in,
+        true,
+        None,
+    )])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        env.balance(&payer, &keys.token_a_mint).await,
+        balance_a - expected_in
+    );
+    assert_eq!(
+        env.balance(&payer, &keys.token_b_mint).await,
+        balance_b + 100_000
+    );
+    assert_eq!(env.pool().await.reserve_b, 20_000_000 - 100_000);
+}
+
+#[tokio::test]
+async fn swap_exact_out_rejects_slippage() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let expected_in = amm_math::swap_exact_out(20_000_000, 10_000_000, 50_000, FEE_BPS).unwrap();
+    let result = env
+        .process(&[instructions::swap_exact_out(
+            &payer,
+            &keys,
+            50_000,
+            expected_in - 1,
+            false,
+            None,
+        )])
+        .await;
+
+    assert_eq!(custom_error(result), u32::from(AMMError::SlippageExceeded));
+    assert_eq!(env.pool().await.reserve_a, 10_000_000);
+}
+
+#[tokio::test]
+async fn swap_goes_through_before_its_deadline() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    // The deadline itself is still in time
+    let now = env.now().await;
+    env.process(&[instructions::swap(&payer, &keys, 1_000, 0, true, Some(now))])
+        .await
+        .unwrap();
+
+    assert_eq!(env.pool().await.reserve_a, 10_000_000 + 1_000);
+}
+
+#[tokio::test]
+async fn swap_rejects_an_expired_deadline() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let now = env.now().await;
+    let result = env
+        .process(&[instructions::swap(
+            &payer,
+            &keys,
+            1_000,
+            0,
+            true,
+            Some(now - 1),
+        )])
+        .await;
+
+    assert_eq!(custom_error(result), u32::from(AMMError::Expired));
+    assert_eq!(env.pool().await.reserve_a, 10_000_000);
+}
+
+#[tokio::test]
+async fn pool_halt_stops_swaps_until_lifted() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    env.process(&[instructions::set_pool_status(
+        &payer,
+        &keys,
+        STATUS_SWAP_DISABLED,
+    )])
+    .await
+    .unwrap();
+    let result = env
+        .process(&[instructions::swap(&payer, &keys, 1_000, 0, true, None)])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::OperationHalted));
+
+    // Only swaps are halted, deposits still go through
+    env.process(&[instructions::add_liquidity(
+        &payer, &keys, 1_000, 2_000, 0, None,
+    )])
+    .await
+    .unwrap();
+
+    env.process(&[
+        instructions::set_pool_status(&payer, &keys, 0),
+        instructions::swap(&payer, &keys, 1_000, 0, true, None),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(env.pool().await.reserve_a, 10_000_000 + 2 * 1_000);
+}
+
+#[tokio::test]
+async fn pool_status_rejects_strangers_and_unknown_flags() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let stranger = Keypair::new();
+    let result = env
+        .process_signed(
+            &[instructions::set_pool_status(
+                &stranger.pubkey(),
+                &keys,
+                STATUS_SWAP_DISABLED,
+            )],
+            &stranger,
+        )
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::Unauthorized));
+
+    let result = env
+        .process(&[instructions::set_pool_status(
+            &payer,
+            &keys,
+            STATUS_ALL_DISABLED + 1,
+        )])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::InvalidStatus));
+    assert_eq!(env.pool().await.status, 0);
+}
+
+#[tokio::test]
+async fn global_halt_stops_every_pool_until_lifted() {
+    let mut env = Env::with_decimals(6, 6).await;
+    env.create_pool(10_000_000, 20_000_000).await;
+    let constant_product = env.keys;
+    env.try_create_pool(10_000_000, 10_000_000, 100, 0)
+        .await
+        .unwrap();
+    let (payer, stable) = (env.payer(), env.keys);
+
+    env.process(&[instructions::set_global_status(&payer, STATUS_ALL_DISABLED)])
+        .await
+        .unwrap();
+    for keys in [constant_product, stable] {
+        let result = env
+            .process(&[instructions::swap(&payer, &keys, 1_000, 0, true, None)])
+            .await;
+        assert_eq!(custom_error(result), u32::from(AMMError::OperationHalted));
+    }
+    let result = env
+        .process(&[instructions::remove_liquidity(
+            &payer,
+            &constant_product,
+            1_000,
+            0,
+            0,
+            None,
+        )])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::OperationHalted));
+
+    env.process(&[
+        instructions::set_global_status(&payer, 0),
+        instructions::swap(&payer, &constant_product, 1_000, 0, true, None),
+        instructions::swap(&payer, &stable, 1_000, 0, true, None),
+    ])
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn global_status_rejects_strangers() {
+    let mut env = Env::new().await;
+
+    let stranger = Keypair::new();
+    let result = env
+        .process_signed(
+            &[instructions::set_global_status(
+                &stranger.pubkey(),
+                STATUS_ALL_DISABLED,
+            )],
+            &stranger,
+        )
+        .await;
+
+    assert_eq!(custom_error(result), u32::from(AMMError::Unauthorized));
+    assert_eq!(env.config().await.status, 0);
+}
+
+// Creates a 10M / 20M pool and swaps 100_000 A for B, so the vault of A also holds protocol fees
+async fn create_pool_with_protocol_fees(env: &mut Env) -> u64 {
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+    env.process(&[instructions::swap(&payer, &keys, 100_000, 0, true, None)])
+        .await
+        .unwrap();
+
+    let protocol_fees_a = env.pool().await.protocol_fees_a;
+    assert!(protocol_fees_a > 0);
+    protocol_fees_a
+}
+
+#[tokio::test]
+async fn sync_raises_the_reserves_to_the_vault_balances() {
+    let mut env = Env::new().await;
+    let keys = env.keys;
+    let protocol_fees_a = create_pool_with_protocol_fees(&mut env).await;
+    let before = env.pool().await;
+
+    env.donate(&keys.vault_a, &keys.token_a_mint, 3_000).await;
+    env.donate(&keys.vault_b, &keys.token_b_mint, 7_000).await;
+    env.process(&[instructions::sync(&keys)]).await.unwrap();
+
+    // The donations go to the reserves, the protocol fees stay set aside
+    let pool = env.pool().await;
+    assert_eq!(pool.reserve_a, before.reserve_a + 3_000);
+    assert_eq!(pool.reserve_b, before.reserve_b + 7_000);
+    assert_eq!(
+        pool.reserve_a,
+        env.token_account_amount(&keys.vault_a).await - protocol_fees_a
+    );
+    assert_eq!(
+        pool.reserve_b,
+        env.token_account_amount(&keys.vault_b).await
+    );
+    assert_eq!(pool.protocol_fees_a, protocol_fees_a);
+}
+
+#[tokio::test]
+async fn skim_sends_only_the_surplus_to_the_recipient() {
+    let mut env = Env::new().await;
+    let keys = env.keys;
+    let protocol_fees_a = create_pool_with_protocol_fees(&mut env).await;
+    let before = env.pool().await;
+    let recipient = Pubkey::new_unique();
+    for mint in [keys.token_a_mint, keys.token_b_mint] {
+        env.fund(&recipient, &mint, 0).await;
+    }
+
+    env.donate(&keys.vault_a, &keys.token_a_mint, 3_000).await;
+    env.donate(&keys.vault_b, &keys.token_b_mint, 7_000).await;
+    let payer = env.payer();
+    env.process(&[instructions::skim(&payer, &keys, &recipient)])
+        .await
+        .unwrap();
+
+    // Only the donations leave, the reserves and the protocol fees are still in the vaults
+    assert_eq!(env.balance(&recipient, &keys.token_a_mint).await, 3_000);
+    assert_eq!(env.balance(&recipient, &keys.token_b_mint).await, 7_000);
+    assert_eq!(
+        env.token_account_amount(&keys.vault_a).await,
+        before.reserve_a + protocol_fees_a
+    );
+    assert_eq!(
+        env.token_account_amount(&keys.vault_b).await,
+        before.reserve_b
+    );
+    let pool = env.pool().await;
+    assert_eq!(
+        (pool.reserve_a, pool.reserve_b),
+        (before.reserve_a, before.reserve_b)
+    );
+    assert_eq!(pool.protocol_fees_a, protocol_fees_a);
+}
+
+#[tokio::test]
+async fn sync_rejects_a_vault_of_another_account() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    // The payer's own token account can't stand in for the pool's vault
+    let mut sync = instructions::sync(&keys);
+    sync.accounts[3].pubkey = keys.associated_token_address(&payer, &keys.token_a_mint);
+    let result = env.process(&[sync]).await;
+
+    assert_eq!(custom_error(result), ErrorCode::ConstraintSeeds as u32);
+    assert_eq!(env.pool().await.reserve_a, 10_000_000);
+}
+
+#[tokio::test]
+async fn skim_rejects_a_recipient_of_the_wrong_mint() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+    env.donate(&keys.vault_a, &keys.token_a_mint, 3_000).await;
+
+    // Token A's surplus can't be paid into a token account of B
+    let mut skim = instructions::skim(&payer, &keys, &payer);
+    skim.accounts[6].pubkey = skim.accounts[7].pubkey;
+    let result = env.process(&[skim]).await;
+
+    assert_eq!(custom_error(result), ErrorCode::ConstraintTokenMint as u32);
+    assert_eq!(
+        env.token_account_amount(&keys.vault_a).await,
+        10_000_000 + 3_000
+    );
+}
+
+#[tokio::test]
+async fn collect_protocol_fees_pays_the_treasury() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    let treasury = Pubkey::new_unique();
+    env.process(&[instructions::update_config(
+        &payer,
+        None,
+        None,
+        Some(treasury),
+        None,
+    )])
+    .await
+    .unwrap();
+    let protocol_fees_a = create_pool_with_protocol_fees(&mut env).await;
+    let reserve_a = env.pool().await.reserve_a;
+
+    // Anyone can send the fees to the treasury, its token accounts are created on the way
+    env.process(&[instructions::collect_protocol_fees(
+        &payer, &keys, &treasury,
+    )])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        env.balance(&treasury, &keys.token_a_mint).await,
+        protocol_fees_a
+    );
+    assert_eq!(env.balance(&treasury, &keys.token_b_mint).await, 0);
+    assert_eq!(env.token_account_amount(&keys.vault_a).await, reserve_a);
+    let pool = env.pool().await;
+    assert_eq!((pool.protocol_fees_a, pool.protocol_fees_b), (0, 0));
+}
+
+#[tokio::test]
+async fn collect_protocol_fees_rejects_another_treasury_and_no_fees() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    let protocol_fees_a = create_pool_with_protocol_fees(&mut env).await;
+
+    let result = env
+        .process(&[instructions::collect_protocol_fees(
+            &payer,
+            &keys,
+            &Pubkey::new_unique(),
+        )])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::Unauthorized));
+    assert_eq!(env.pool().await.protocol_fees_a, protocol_fees_a);
+
+    // The payer is the config's treasury, once collected there is nothing left
+    env.process(&[instructions::collect_protocol_fees(&payer, &keys, &payer)])
+        .await
+        .unwrap();
+    let result = env
+        .process(&[instructions::collect_protocol_fees(&payer, &keys, &payer)])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::NoProtocolFees));
+}
+
+#[tokio::test]
+async fn stable_swap_pays_out_the_quoted_amount() {
+    let mut env = Env::with_decimals(6, 6).await;
+    env.try_create_pool(10_000_000, 10_000_000, 100, 0)
+        .await
+        .unwrap();
+    let (payer, keys) = (env.payer(), env.keys);
+
+    // The invariant of a balanced pool is the sum of its reserves
+    assert_eq!(env.lp_supply().await, 20_000_000);
+    assert_eq!(env.pool().await.target_amp, 100);
+
+    let expected_out =
+        amm_math::stable_swap_exact_in(100, 10_000_000, 10_000_000, 100_000, FEE_BPS).unwrap();
+    assert!(
+        expected_out > amm_math::swap_exact_in(10_000_000, 10_000_000, 100_000, FEE_BPS).unwrap()
+    );
+    let balance_b = env.balance(&payer, &keys.token_b_mint).await;
+
+    env.process(&[instructions::swap(
+        &payer,
+        &keys,
+        100_000,
+        expected_out,
+        true,
+        None,
+    )])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        env.balance(&payer, &keys.token_b_mint).await,
+        balance_b + expected_out
+    );
+    assert_eq!(env.pool().await.reserve_b, 10_000_000 - expected_out);
+}
+
+#[tokio::test]
+async fn stable_pool_skims_donations() {
+    let mut env = Env::with_decimals(6, 6).await;
+    env.try_create_pool(10_000_000, 10_000_000, 100, 0)
+        .await
+        .unwrap();
+    let (payer, keys) = (env.payer(), env.keys);
+    let recipient = Pubkey::new_unique();
+    for mint in [keys.token_a_mint, keys.token_b_mint] {
+        env.fund(&recipient, &mint, 0).await;
+    }
+
+    // The pool signs for its vaults with the curve byte in its seeds
+    env.donate(&keys.vault_b, &keys.token_b_mint, 5_000).await;
+    env.process(&[instructions::skim(&payer, &keys, &recipient)])
+        .await
+        .unwrap();
+
+    assert_eq!(env.balance(&recipient, &keys.token_b_mint).await, 5_000);
+    assert_eq!(env.token_account_amount(&keys.vault_b).await, 10_000_000);
+}
+
+#[tokio::test]
+async fn stable_pool_rejects_mismatched_decimals() {
+    let mut env = Env::new().await;
+
+    let result = env.try_create_pool(10_000_000, 10_000_000, 100, 0).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(AMMError::MismatchedDecimals)
+    );
+}
+
+#[tokio::test]
+async fn amp_ramps_linearly_and_stops() {
+    let mut env = Env::with_decimals(6, 6).await;
+    env.try_create_pool(10_000_000, 10_000_000, 100, 0)
+        .await
+        .unwrap();
+    let (payer, keys) = (env.payer(), env.keys);
+
+    // Pinned so the ramp starts at exactly `start`
+    let start = env.now().await;
+    env.warp_to_timestamp(start).await;
+
+    // More than 10x in one ramp or a ramp shorter than a day are rejected
+    let result = env
+        .process(&[instructions::ramp_amp(
+            &payer,
+            &keys,
+            1_001,
+            start + 2 * DAY,
+        )])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::AmpChangeTooFast));
+    let result = env
+        .process(&[instructions::ramp_amp(&payer, &keys, 200, start + DAY - 1)])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::AmpChangeTooFast));
+
+    env.process(&[instructions::ramp_amp(&payer, &keys, 200, start + 2 * DAY)])
+        .await
+        .unwrap();
+
+    // Halfway through the ramp, swaps price with the interpolated amp
+    env.warp_to_timestamp(start + DAY).await;
+    assert_eq!(env.pool().await.current_amp(start + DAY), 150);
+    let expected_out =
+        amm_math::stable_swap_exact_in(150, 10_000_000, 10_000_000, 100_000, FEE_BPS).unwrap();
+    env.process(&[instructions::swap(
+        &payer,
+        &keys,
+        100_000,
+        expected_out,
+        true,
+        None,
+    )])
+    .await
+    .unwrap();
+    assert_eq!(env.pool().await.reserve_b, 10_000_000 - expected_out);
+
+    // A new ramp has to wait a day after the previous one started
+    env.warp_to_timestamp(start + DAY - 1).await;
+    let result = env
+        .process(&[instructions::ramp_amp(&payer, &keys, 300, start + 3 * DAY)])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::RampTooSoon));
+
+    // Stopping freezes the amp where the ramp got to
+    env.warp_to_timestamp(start + DAY).await;
+    env.process(&[instructions::stop_ramp_amp(&payer, &keys)])
+        .await
+        .unwrap();
+    let pool = env.pool().await;
+    assert_eq!((pool.initial_amp, pool.target_amp), (150, 150));
+    assert_eq!(pool.current_amp(start + 3 * DAY), 150);
+}
+
+#[tokio::test]
+async fn constant_product_pools_can_not_ramp() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    let start = env.now().await;
+    let result = env
+        .process(&[instructions::ramp_amp(&payer, &keys, 100, start + 2 * DAY)])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::NotStablePool));
+}
+
+#[tokio::test]
+async fn weighted_swap_prices_with_the_weights() {
+    let mut env = Env::new().await;
+    env.try_create_pool(4_000_000, 250_000, 0, 8_000)
+        .await
+        .unwrap();
+    let (payer, keys) = (env.payer(), env.keys);
+
+    let pool = env.pool().await;
+    assert_eq!((pool.weight_a_bps, pool.weight_b_bps), (8_000, 2_000));
+    assert_eq!(
+        env.lp_supply().await,
+        amm_math::weighted_initial_liquidity(4_000_000, 8_000, 250_000, 2_000).unwrap()
+    );
+
+    // 80% of the value sits in A, 1 A is worth 0.25 B instead of the 1/16th a 50/50 pool would pay. The
+    // weight ratio also steepens the curve, so the trade pays a bit less than 4x and the 0.25 spot price
+    let expected_out =
+        amm_math::weighted_swap_exact_in(4_000_000, 8_000, 250_000, 2_000, 100_000, FEE_BPS)
+            .unwrap();
+    let constant_product_out =
+        amm_math::swap_exact_in(4_000_000, 250_000, 100_000, FEE_BPS).unwrap();
+    assert!(expected_out > 3 * constant_product_out && expected_out < 100_000 / 4);
+    let balance_b = env.balance(&payer, &keys.token_b_mint).await;
+
+    env.process(&[instructions::swap(
+        &payer,
+        &keys,
+        100_000,
+        expected_out,
+        true,
+        None,
+    )])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        env.balance(&payer, &keys.token_b_mint).await,
+        balance_b + expected_out
+    );
+    assert_eq!(env.pool().await.reserve_b, 250_000 - expected_out);
+}
+
+#[tokio::test]
+async fn weighted_pool_rejects_invalid_weights() {
+    let mut env = Env::with_decimals(6, 6).await;
+
+    let result = env.try_create_pool(4_000_000, 250_000, 0, 9_950).await;
+    assert_eq!(custom_error(result), u32::from(AMMError::InvalidWeights));
+
+    // A pool is either stable or weighted
+    let result = env.try_create_pool(4_000_000, 250_000, 100, 8_000).await;
+    assert_eq!(custom_error(result), u32::from(AMMError::InvalidWeights));
+}
+
+#[tokio::test]
+async fn add_then_remove_liquidity_round_trips() {
+    let mut env = Env::new().await;
+    let (payer, keys) = (env.payer(), env.keys);
+    env.create_pool(10_000_000, 20_000_000).await;
+
+    // A second provider deposits at the pool's ratio, the extra token B is left untouched
+    let provider = Keypair::new();
+    let user = provider.pubkey();
+    env.process(&[system_instruction::transfer(&payer, &user, 1_000_000_000)])
+        .await
+        .unwrap();
+    env.fund(&user, &keys.token_a_mint, 1_000_000).await;
+    env.fund(&user, &keys.token_b_mint, 3_000_000).await;
+
+    let lp_supply = env.lp_supply().await;
+    let deposit =
+        amm_math::deposit_shares(1_000_000, 3_000_000, 10_000_000, 20_000_000, lp_supply).unwrap();
+    assert!(deposit.amount_b < 3_000_000);
+
+    let add =
+        instructions::add_liquidity(&user, &keys, 1_000_000, 3_000_000, deposit.lp_minted, None);
+    env.process_signed(&[add], &provider).await.unwrap();
+
+    assert_eq!(env.balance(&user, &keys.lp_mint).await, deposit.lp_minted);
+    assert_eq!(
+        env.balance(&user, &keys.token_a_mint).await,
+        1_000_000 - deposit.amount_a
+    );
+    assert_eq!(
+        env.balance(&user, &keys.token_b_mint).await,
+        3_000_000 - deposit.amount_b
+    );
+    assert_eq!(env.lp_supply().await, lp_supply + deposit.lp_minted);
+
+    // Burning everything gives back at most what was deposited, rounded down in favor of the pool
+    let pool = env.pool().await;
+    let withdrawal = amm_math::withdrawal_amounts(
+        deposit.lp_minted,
+        pool.reserve_a,
+        pool.reserve_b,
+        lp_supply + deposit.lp_minted,
+    )
+    .unwrap();
+    assert!(withdrawal.amount_a <= deposit.amount_a && withdrawal.amount_b <= deposit.amount_b);
+
+    let remove = instructions::remove_liquidity(
+        &user,
+        &keys,
+        deposit.lp_minted,
+        withdrawal.amount_a,
+        withdrawal.amount_b,
+        None,
+    );
+    env.process_signed(&[remove], &provider).await.unwrap();
+
+    assert_eq!(
+        env.balance(&user, &keys.token_a_mint).await,
+        1_000_000 - deposit.amount_a + withdrawal.amount_a
+    );
+    assert_eq!(
+        env.balance(&user, &keys.token_b_mint).await,
+        3_000_000 - deposit.amount_b + withdrawal.amount_b
+    );
+    assert_eq!(env.balance(&user, &keys.lp_mint).await, 0);
+    assert_eq!(env.lp_supply().await, lp_supply);
+
+    // The tracked reserves still match the vaults, there are no protocol fees without swaps
+    let pool = env.pool().await;
+    assert_eq!(
+        pool.reserve_a,
+        env.token_account_amount(&keys.vault_a).await
+    );
+    assert_eq!(
+        pool.reserve_b,
+        env.token_account_amount(&keys.vault_b).await
+    );
+}
+
+#[tokio::test]
+async fn concentrated_liquidity_positions_hold_their_range() {
+    let mut env = Env::new().await;
+    let liquidity = 1_000_000_000u128;
+    create_concentrated_pool(&mut env, liquidity).await;
+    let keys = env.keys;
+
+    // Only the position around the price is in range, the one below it is all token B
+    let sqrt_price = |tick| amm_math::sqrt_price_at_tick(tick).unwrap();
+    let (around_a, around_b) = amm_math::amounts_for_liquidity(
+        1 << 64,
+        sqrt_price(-100),
+        sqrt_price(100),
+        liquidity,
+        true,
+    )
+    .unwrap();
+    let (below_a, below_b) = amm_math::amounts_for_liquidity(
+        1 << 64,
+        sqrt_price(-200),
+        sqrt_price(-100),
+        liquidity,
+        true,
+    )
+    .unwrap();
+    assert_eq!(below_a, 0);
+    assert_eq!(
+        env.token_account_amount(&keys.vault_a).await,
+        around_a + below_a
+    );
+    assert_eq!(
+        env.token_account_amount(&keys.vault_b).await,
+        around_b + below_b
+    );
+
+    let pool = env.pool().await;
+    assert_eq!((pool.tick_current, pool.liquidity), (0, liquidity));
+    assert_eq!(pool.lp_mint, Pubkey::default());
+
+    // The constant product pool instructions don't apply to it
+    let payer = env.payer();
+    let result = env
+        .process(&[instructions::swap(&payer, &keys, 1_000, 0, true, None)])
+        .await;
+    assert_eq!(custom_error(result), u32::from(AMMError::WrongPoolType));
+}
+
+#[tokio::test]
+async fn concentrated_swap_crosses_ticks_and_pays_the_fees() {
+    let mut env = Env::new().await;
+    let liquidity = 1_000_000_000u128;
+    let [around, below] = create_concentrated_pool(&mut env, liquidity).await;
+    let (payer, keys) = (env.payer(), env.keys);
+
+    // Selling 6M of A takes the price past tick -100, where the position below takes over
+    let tick_arrays = instructions::swap_tick_arrays(&keys, TICK_SPACING, 0, true, 2);
+    let balance_b = env.balance(&payer, &keys.token_b_mint).await;
+    env.process(&[instructions::swap_concentrated(
+        &payer,
+        &keys,
+        &tick_arrays,
+        6_000_000,
+        5_000_000,
+        true,
+        None,
+    )])
+    .await
+    .unwrap();
+
+    let pool = env.pool().await;
+    assert!(pool.tick_current < -100 && pool.tick_current > -200);
+    assert_eq!(pool.liquidity, liquidity);
+    assert!(pool.fee_growth_global_a > 0 && pool.fee_growth_global_b == 0);
+    let amount_out = env.balance(&payer, &keys.token_b_mint).await - balance_b;
+    assert!((5_000_000..6_000_000).contains(&amount_out));
+    assert_eq!(
+        pool.reserve_b,
+        env.token_account_amount(&keys.vault_b).await
+    );
+
+    // Both positions earned fees in A, paid out with their liquidity
+    for position in [around, below] {
+        let balance_a = env.balance(&payer, &keys.token_a_mint).await;
+        env.process(&[instructions::decrease_liquidity(
+            &payer, &keys, &position, liquidity, 0, 0, None,
+        )])
+        .await
+        .unwrap();
+        assert!(env.balance(&payer, &keys.token_a_mint).await > balance_a);
+    }
+
+    // Only the protocol fees and the rounding dust are left
+    let pool = env.pool().await;
+    assert_eq!(pool.liquidity, 0);
+    assert!(env.token_account_amount(&keys.vault_a).await - pool.protocol_fees_a < 10);
+    assert!(env.token_account_amount(&keys.vault_b).await < 10);
+}
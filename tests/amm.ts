@@ -9,7 +9,7 @@ import {
   getAccount,
   getAssociatedTokenAddressSync,
 } from "@solana/spl-token"
-import { PublicKey } from "@solana/web3.js"
+import { Keypair, PublicKey } from "@solana/web3.js"
 
 describe("amm", () => {
   // Set the Provider and Program
@@ -31,6 +31,26 @@ describe("amm", () => {
   let lpMint: PublicKey
   let userLpAccount: PublicKey
 
+  // Pools only exist for the canonical ordering of a pair (lower pubkey as token A),
+  // returns the pair in that order with the `a_to_b` flag for swapping mintIn -> mintOut.
+  // Mirrors `amm_client::canonical_pair`, the ordering `PoolKeys` and the CLI use
+  function normalizePair(mintIn: PublicKey, mintOut: PublicKey) {
+    return Buffer.compare(mintIn.toBuffer(), mintOut.toBuffer()) < 0
+      ? { tokenAMint: mintIn, tokenBMint: mintOut, aToB: true }
+      : { tokenAMint: mintOut, tokenBMint: mintIn, aToB: false }
+  }
+
+  // Pools of the same pair are told apart by their fee tier (u16, little endian)
+  function feeTierSeed(feeBps: number) {
+    return new anchor.BN(feeBps).toArrayLike(Buffer, "le", 2)
+  }
+
+  // ...and by their curve, the index of the program's `CurveType` variant
+  const CurveType = { ConstantProduct: 0, StableSwap: 1, Weighted: 2, Concentrated: 3 }
+  function curveTypeSeed(curveType: number) {
+    return Buffer.from([curveType])
+  }
+
   async function logUserStats(label: string) {
     const sol = await getAccount(connection, userSolAccount)
     const usdc = await getAccount(connection, userUsdcAccount)
@@ -52,11 +72,14 @@ describe("amm", () => {
   }
 
   before("Tokens and Funds setup", async () => {
-    // Create Mint accounts
-    solMint = await createMint(connection, user, user.publicKey, null, 6)
+    // Create Mint accounts, SOL is token A of the canonical SOL/USDC pair
+    const [solMintKeypair, usdcMintKeypair] = [Keypair.generate(), Keypair.generate()].sort((x, y) =>
+      Buffer.compare(x.publicKey.toBuffer(), y.publicKey.toBuffer())
+    )
+    solMint = await createMint(connection, user, user.publicKey, null, 6, solMintKeypair)
     console.log("SOL Mint Account Created: ", solMint)
 
-    usdcMint = await createMint(connection, user, user.publicKey, null, 6)
+    usdcMint = await createMint(connection, user, user.publicKey, null, 6, usdcMintKeypair)
     console.log("USDC Mint Account Created: ", usdcMint)
 
     // Create User Token Accounts
@@ -74,35 +97,45 @@ describe("amm", () => {
 
     // Derive PDA's for Pools, Vaults, LP_tokens
     ;[poolAddr] = PublicKey.findProgramAddressSync(
-      [Buffer.from("pool"), solMint.toBuffer(), usdcMint.toBuffer()],
+      [Buffer.from("pool"), solMint.toBuffer(), usdcMint.toBuffer(), feeTierSeed(30), curveTypeSeed(CurveType.ConstantProduct)],
       program.programId
     )
     console.log(`\n\nPool Address Derived: ${poolAddr}`)
-    ;[vaultAddrA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("vault_a"), solMint.toBuffer(), usdcMint.toBuffer()],
-      program.programId
-    )
-    ;[vaultAddrB] = PublicKey.findProgramAddressSync(
-      [Buffer.from("vault_b"), solMint.toBuffer(), usdcMint.toBuffer()],
-      program.programId
-    )
-    ;[lpMint] = PublicKey.findProgramAddressSync(
-      [Buffer.from("lp_mint"), solMint.toBuffer(), usdcMint.toBuffer()],
-      program.programId
-    )
+    ;[vaultAddrA] = PublicKey.findProgramAddressSync([Buffer.from("vault_a"), poolAddr.toBuffer()], program.programId)
+    ;[vaultAddrB] = PublicKey.findProgramAddressSync([Buffer.from("vault_b"), poolAddr.toBuffer()], program.programId)
+    ;[lpMint] = PublicKey.findProgramAddressSync([Buffer.from("lp_mint"), poolAddr.toBuffer()], program.programId)
     userLpAccount = await getAssociatedTokenAddressSync(lpMint, user.publicKey)
     console.log("ATA of LP_token for User created: ", userUsdcAccount)
 
     console.log("PDA's Derived! ")
   })
 
+  it("Initialize Config", async () => {
+    // Only the program's upgrade authority, the deploying wallet, can create the config
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    )
+
+    // Call Method
+    const initializeConfigTx = await program.methods
+      .initializeConfig(user.publicKey)
+      .accounts({
+        admin: user.publicKey,
+        programData,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("Config Intialized: ", initializeConfigTx)
+  })
+
   it("Initialize Pool", async () => {
     const initialLiquiditySOL = new anchor.BN(1000 * 10 ** 6)
     const initialLiquidityUSDC = new anchor.BN(5000 * 10 ** 6)
 
     // Call Method
     const initializePoolTx = await program.methods
-      .initializePool(initialLiquiditySOL, initialLiquidityUSDC)
+      .initializePool(initialLiquiditySOL, initialLiquidityUSDC, 30, new anchor.BN(0), 0)
       .accounts({
         creator: user.publicKey,
         tokenAMint: solMint,
@@ -119,50 +152,249 @@ describe("amm", () => {
     await logPoolState()
   })
 
+  it("Second Pool with a different fee tier for the same pair", async () => {
+    const [stablePoolAddr] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), solMint.toBuffer(), usdcMint.toBuffer(), feeTierSeed(5), curveTypeSeed(CurveType.ConstantProduct)],
+      program.programId
+    )
+
+    // Call Method
+    const initializePoolTx = await program.methods
+      .initializePool(new anchor.BN(100 * 10 ** 6), new anchor.BN(500 * 10 ** 6), 5, new anchor.BN(0), 0)
+      .accounts({
+        creator: user.publicKey,
+        tokenAMint: solMint,
+        tokenBMint: usdcMint,
+        creatorTokenA: userSolAccount,
+        creatorTokenB: userUsdcAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("5 bps Pool Intialized: ", initializePoolTx)
+
+    const stablePool = await program.account.pool.fetch(stablePoolAddr)
+    console.log("Fee of the second pool (bps): ", stablePool.feeBps)
+  })
+
+  it("Stable pool with an amplification coefficient", async () => {
+    const [ampPoolAddr] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), solMint.toBuffer(), usdcMint.toBuffer(), feeTierSeed(1), curveTypeSeed(CurveType.StableSwap)],
+      program.programId
+    )
+
+    // Call Method
+    const initializePoolTx = await program.methods
+      .initializePool(new anchor.BN(100 * 10 ** 6), new anchor.BN(100 * 10 ** 6), 1, new anchor.BN(100), 0)
+      .accounts({
+        creator: user.publicKey,
+        tokenAMint: solMint,
+        tokenBMint: usdcMint,
+        creatorTokenA: userSolAccount,
+        creatorTokenB: userUsdcAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("Stable Pool Intialized: ", initializePoolTx)
+
+    const ampPool = await program.account.pool.fetch(ampPoolAddr)
+    console.log("Amplification coefficient of the stable pool: ", ampPool.targetAmp.toString())
+    if (!ampPool.targetAmp.eq(new anchor.BN(100))) throw new Error("Stable pool stored the wrong amp")
+
+    // The amp can only ramp over at least a day
+    try {
+      await program.methods
+        .rampAmp(new anchor.BN(200), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+        .accounts({
+          admin: user.publicKey,
+          liquidityPool: ampPoolAddr,
+        })
+        .rpc({ commitment: "confirmed" })
+      throw new Error("Amp ramped within a minute")
+    } catch (err) {
+      console.log("Ramp rejected: ", err.error?.errorCode?.code)
+      if (err.error?.errorCode?.code !== "AmpChangeTooFast") throw err
+    }
+  })
+
+  it("Weighted 80/20 pool", async () => {
+    const [weightedPoolAddr] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), solMint.toBuffer(), usdcMint.toBuffer(), feeTierSeed(100), curveTypeSeed(CurveType.Weighted)],
+      program.programId
+    )
+
+    // Call Method
+    const initializePoolTx = await program.methods
+      .initializePool(new anchor.BN(400 * 10 ** 6), new anchor.BN(25 * 10 ** 6), 100, new anchor.BN(0), 8_000)
+      .accounts({
+        creator: user.publicKey,
+        tokenAMint: solMint,
+        tokenBMint: usdcMint,
+        creatorTokenA: userSolAccount,
+        creatorTokenB: userUsdcAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("Weighted Pool Intialized: ", initializePoolTx)
+
+    const weightedPool = await program.account.pool.fetch(weightedPoolAddr)
+    console.log("Weights of the weighted pool (bps): ", weightedPool.weightABps, weightedPool.weightBBps)
+    if (weightedPool.weightABps !== 8_000 || weightedPool.weightBBps !== 2_000) {
+      throw new Error("Weighted pool stored the wrong weights")
+    }
+  })
+
   it("User Swaps token from (SOL -> USDC)", async () => {
     console.log("\n\n\n Test : Swap SOL -> USDC")
     const solToSwap = new anchor.BN(100 * 10 ** 6)
     const minimumUSDCTolerance = new anchor.BN(450 * 10 ** 6)
+    const { tokenAMint, tokenBMint, aToB } = normalizePair(solMint, usdcMint)
 
     // Call Method
     const swapAToBTx = await program.methods
-      .swap(solToSwap, minimumUSDCTolerance, true)
-      .accounts({
+      .swap(solToSwap, minimumUSDCTolerance, aToB, null)
+      .accountsPartial({
         signer: user.publicKey,
-        tokenAMint: solMint,
-        tokenBMint: usdcMint,
+        tokenAMint,
+        tokenBMint,
+        liquidityPool: poolAddr,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc({ commitment: "confirmed" })
 
     console.log("\nSwap Complete: ", swapAToBTx)
 
+    // Indexers read the Swapped event instead of the token transfers
+    const tx = await connection.getTransaction(swapAToBTx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    })
+    const eventParser = new anchor.EventParser(program.programId, program.coder)
+    for (const event of eventParser.parseLogs(tx.meta.logMessages)) {
+      console.log(`Event ${event.name}: `, event.data)
+    }
+
     await logUserStats("After Swap")
     await logPoolState()
   })
 
+  it("Quoted swap matches the executed swap", async () => {
+    const solToSwap = new anchor.BN(10 * 10 ** 6)
+    const { tokenAMint, tokenBMint, aToB } = normalizePair(solMint, usdcMint)
+
+    // Simulated, no tokens are moved
+    const quote = await program.methods
+      .quoteSwap(solToSwap, aToB)
+      .accountsPartial({ tokenAMint, tokenBMint, liquidityPool: poolAddr })
+      .view()
+    console.log("Quoted USDC out: ", quote.amountOut.toString())
+
+    const usdcBefore = await getAccount(connection, userUsdcAccount)
+    await program.methods
+      .swap(solToSwap, quote.amountOut, aToB, null)
+      .accountsPartial({
+        signer: user.publicKey,
+        tokenAMint,
+        tokenBMint,
+        liquidityPool: poolAddr,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+    const usdcAfter = await getAccount(connection, userUsdcAccount)
+
+    const received = usdcAfter.amount - usdcBefore.amount
+    if (received.toString() !== quote.amountOut.toString()) {
+      throw new Error(`Quoted ${quote.amountOut} but received ${received}`)
+    }
+  })
+
   it("User Swaps token from (USDC -> SOL)", async () => {
     console.log("\n\n\n Test : Swap USDC -> SOL")
     const usdcToSwap = new anchor.BN(1000 * 10 ** 6)
     const minimumSOLTolerance = new anchor.BN(150 * 10 ** 6)
+    const { tokenAMint, tokenBMint, aToB } = normalizePair(usdcMint, solMint)
 
     // Call Method
     const swapAToBTx = await program.methods
-      .swap(usdcToSwap, minimumSOLTolerance, false)
-      .accounts({
+      .swap(usdcToSwap, minimumSOLTolerance, aToB, null)
+      .accountsPartial({
+        signer: user.publicKey,
+        tokenAMint,
+        tokenBMint,
+        liquidityPool: poolAddr,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("\nSwap Complete: ", swapAToBTx)
+
+    await logUserStats("After Swap")
+    await logPoolState()
+  })
+
+  it("User Swaps for an exact amount of USDC", async () => {
+    console.log("\n\n\n Test : Swap SOL -> exact USDC")
+    const usdcToReceive = new anchor.BN(100 * 10 ** 6)
+    const maximumSOLTolerance = new anchor.BN(30 * 10 ** 6)
+
+    // Call Method
+    const swapExactOutTx = await program.methods
+      .swapExactOut(usdcToReceive, maximumSOLTolerance, true, null)
+      .accountsPartial({
         signer: user.publicKey,
         tokenAMint: solMint,
         tokenBMint: usdcMint,
+        liquidityPool: poolAddr,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc({ commitment: "confirmed" })
 
-    console.log("\nSwap Complete: ", swapAToBTx)
+    console.log("\nSwap Complete: ", swapExactOutTx)
 
     await logUserStats("After Swap")
     await logPoolState()
   })
 
+  it("Guardian halts and resumes swaps on the pool", async () => {
+    console.log("\n\n\n Test : Swaps are rejected while halted")
+    const SWAP_DISABLED = 1
+
+    await program.methods
+      .setPoolStatus(SWAP_DISABLED)
+      .accounts({
+        authority: user.publicKey,
+        liquidityPool: poolAddr,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    try {
+      await program.methods
+        .swap(new anchor.BN(10 * 10 ** 6), new anchor.BN(1), true, null)
+        .accountsPartial({
+          signer: user.publicKey,
+          tokenAMint: solMint,
+          tokenBMint: usdcMint,
+          liquidityPool: poolAddr,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" })
+      throw new Error("Swap went through while halted")
+    } catch (err) {
+      console.log("Swap rejected: ", err.error?.errorCode?.code)
+      if (err.error?.errorCode?.code !== "OperationHalted") throw err
+    }
+
+    await program.methods
+      .setPoolStatus(0)
+      .accounts({
+        authority: user.publicKey,
+        liquidityPool: poolAddr,
+      })
+      .rpc({ commitment: "confirmed" })
+  })
+
   it("User Provides Liquidity to the Pool", async () => {
     console.log("\n\n\n Test : Liquidity provider provides Liquidation to the pool")
     const solToProvide = new anchor.BN(1000 * 10 ** 6)
@@ -170,11 +402,12 @@ describe("amm", () => {
 
     // Call Method
     const addLiquidityTx = await program.methods
-      .addLiquidity(solToProvide, usdcToProvide, new anchor.BN(350 * 10 ** 6))
-      .accounts({
+      .addLiquidity(solToProvide, usdcToProvide, new anchor.BN(350 * 10 ** 6), null)
+      .accountsPartial({
         liquidityProvider: user.publicKey,
         tokenAMint: solMint,
         tokenBMint: usdcMint,
+        liquidityPool: poolAddr,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc({ commitment: "confirmed" })
@@ -183,6 +416,26 @@ describe("amm", () => {
     await logPoolState()
   })
 
+  it("Pool can not be created for the reversed pair", async () => {
+    try {
+      await program.methods
+        .initializePool(new anchor.BN(1000 * 10 ** 6), new anchor.BN(1000 * 10 ** 6), 30, new anchor.BN(0), 0)
+        .accounts({
+          creator: user.publicKey,
+          tokenAMint: usdcMint,
+          tokenBMint: solMint,
+          creatorTokenA: userUsdcAccount,
+          creatorTokenB: userSolAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" })
+      throw new Error("Pool created for the reversed pair")
+    } catch (err) {
+      console.log("Pool creation rejected: ", err.error?.errorCode?.code)
+      if (err.error?.errorCode?.code !== "InvalidMintOrder") throw err
+    }
+  })
+
   it("Revoking Liquidity from the pool", async () => {
     console.log("\n\n\n Test : Liquidity provider revokes Liquidation from the pool")
     const lpTokenMinted = await getAccount(connection, userLpAccount)
@@ -190,11 +443,12 @@ describe("amm", () => {
 
     // Call Method
     const removeLiquidityTx = await program.methods
-      .removeLiquidity(new anchor.BN(lpTokenMinted.amount), new anchor.BN(1), new anchor.BN(1))
-      .accounts({
+      .removeLiquidity(new anchor.BN(lpTokenMinted.amount), new anchor.BN(1), new anchor.BN(1), null)
+      .accountsPartial({
         liquidityRevoker: user.publicKey,
         tokenAMint: solMint,
         tokenBMint: usdcMint,
+        liquidityPool: poolAddr,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc({ commitment: "confirmed" })
@@ -202,4 +456,53 @@ describe("amm", () => {
     await logUserStats("After Swap")
     await logPoolState()
   })
+
+  it("Treasury collects the protocol fees", async () => {
+    console.log("\n\n\n Test : Protocol fees are collected to the treasury")
+    const poolBefore = await program.account.pool.fetch(poolAddr)
+    console.log("Protocol fees accrued (SOL): ", poolBefore.protocolFeesA.toString())
+    console.log("Protocol fees accrued (USDC): ", poolBefore.protocolFeesB.toString())
+
+    // Call Method
+    const collectProtocolFeesTx = await program.methods
+      .collectProtocolFees()
+      .accountsPartial({
+        collector: user.publicKey,
+        tokenAMint: solMint,
+        tokenBMint: usdcMint,
+        liquidityPool: poolAddr,
+        treasury: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("\nProtocol Fees Collected: ", collectProtocolFeesTx)
+
+    await logPoolState()
+  })
+
+  it("Donations are skimmed out of the pool", async () => {
+    console.log("\n\n\n Test : Tokens sent straight to the vault are not part of the reserves")
+    await mintTo(connection, user, solMint, vaultAddrA, user, 10 * 10 ** 6)
+
+    // Call Method
+    const skimTx = await program.methods
+      .skim()
+      .accountsPartial({
+        skimmer: user.publicKey,
+        tokenAMint: solMint,
+        tokenBMint: usdcMint,
+        liquidityPool: poolAddr,
+        recipientTokenA: userSolAccount,
+        recipientTokenB: userUsdcAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    console.log("\nSkim Complete: ", skimTx)
+
+    const pool = await program.account.pool.fetch(poolAddr)
+    const vault_a = await getAccount(connection, vaultAddrA)
+    console.log("Reserve A: ", pool.reserveA.toString(), " Vault A: ", vault_a.amount.toString())
+  })
 })